@@ -0,0 +1,23 @@
+//! Exposes the resolved solana-sdk and spl-token versions from Cargo.lock to
+//! the `/version` endpoint.
+use std::{env, fs, path::Path};
+
+fn locked_version(lock: &str, name: &str) -> String {
+    let lock = lock.replace("\r\n", "\n");
+    let header = format!("name = \"{name}\"\nversion = \"");
+    lock.find(&header)
+        .and_then(|start| {
+            let rest = &lock[start + header.len()..];
+            rest.find('"').map(|end| rest[..end].to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let lock_path = Path::new(&manifest_dir).join("../Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock_path.display());
+    let lock = fs::read_to_string(&lock_path).unwrap_or_default();
+    println!("cargo:rustc-env=SOLANA_SDK_VERSION={}", locked_version(&lock, "solana-sdk"));
+    println!("cargo:rustc-env=SPL_TOKEN_VERSION={}", locked_version(&lock, "spl-token"));
+}
@@ -1,59 +1,355 @@
+use clap::Parser;
 use dotenv::dotenv;
 use poem::{
-    handler, listener::TcpListener, web::Json, Route, Server, IntoResponse, http::StatusCode
+    handler, listener::TcpListener, middleware::{CatchPanic, Cors}, web::{Compress, CompressionAlgo, Data, Html, Json, Path, Query}, Body, Endpoint,
+    EndpointExt, error::{ParseJsonError, ReadBodyError}, IntoResponse, PathPattern, Request, Response, Route, Server,
+    http::{header, Method, StatusCode},
+};
+use schemars::{gen::{SchemaGenerator, SchemaSettings}, JsonSchema};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_request::RpcRequest,
+    rpc_response::Response as RpcResponse,
 };
-use serde::{Deserialize, Serialize};
 use solana_sdk::{
-    pubkey::Pubkey,
+    address_lookup_table::{instruction as lut_instruction, state::LOOKUP_TABLE_MAX_ADDRESSES},
+    address_lookup_table_account::AddressLookupTableAccount,
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    derivation_path::DerivationPath,
+    hash::{self, Hash},
+    instruction::{AccountMeta, Instruction},
+    message::{v0, Message, VersionedMessage},
+    native_token::LAMPORTS_PER_SOL,
+    packet::PACKET_DATA_SIZE,
+    nonce::State as NonceState,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::{Pubkey, MAX_SEEDS, MAX_SEED_LEN},
+    rent::Rent,
     signature::{Keypair, Signer, Signature},
-    system_instruction,
+    signer::keypair::{keypair_from_seed, keypair_from_seed_and_derivation_path},
+    stake::{
+        instruction as stake_instruction,
+        state::{Authorized, Lockup},
+    },
+    system_instruction::{self, MAX_PERMITTED_DATA_LENGTH},
+    transaction::VersionedTransaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address,
+    instruction::{create_associated_token_account, create_associated_token_account_idempotent},
 };
-use spl_token::instruction as token_instruction;
+use spl_token::instruction::{self as token_instruction, AuthorityType, TokenInstruction};
+use spl_token::state::{Account as TokenAccount, Mint};
+use std::collections::HashMap;
 use std::env;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, RwLock};
 use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use base58::{ToBase58, FromBase58};
 use base64::{Engine as _, engine::general_purpose};
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder};
+use tracing::Instrument;
+use tracing_subscriber::EnvFilter;
+use uuid::Uuid;
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct ApiResponse {
     success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     data: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
+    #[serde(rename = "errorCode", skip_serializing_if = "Option::is_none")]
+    error_code: Option<ErrorCode>,
+    /// Filled in by `log_request`; matches the `x-request-id` response header.
+    #[serde(rename = "requestId", skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+}
+
+/// Stable, machine-readable error categories. The variant names are part of
+/// the API contract; extend rather than rename.
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum ErrorCode {
+    MissingFields,
+    InvalidPubkey,
+    InvalidSigner,
+    ZeroAmount,
+    InvalidAmount,
+    InvalidSecret,
+    InvalidSignature,
+    InvalidTransaction,
+    InvalidInput,
+    InstructionError,
+    SimulationFailed,
+    RpcError,
+    ServiceUnavailable,
+    Unauthorized,
+    RateLimited,
+    Timeout,
+    PayloadTooLarge,
+    UnsupportedMediaType,
+    InternalError,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum ApiError {
+    #[error("Missing required fields")]
+    MissingFields,
+    #[error("Invalid public key(s)")]
+    InvalidPubkey,
+    #[error("{0} is off the ed25519 curve and cannot sign")]
+    OffCurve(Pubkey),
+    #[error("Amount must be greater than zero")]
+    ZeroAmount,
+    #[error("{0}")]
+    InvalidAmount(String),
+    #[error("Invalid secret key")]
+    InvalidSecret,
+    #[error("Secret key must be 32 or 64 bytes, got {0}")]
+    SecretLength(usize),
+    #[error("Invalid signature or public key")]
+    InvalidSignature,
+    #[error("Invalid transaction")]
+    InvalidTransaction,
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("Failed to create instruction: {0}")]
+    Instruction(String),
+    #[error("Operation {0}: {1}")]
+    Operation(usize, Box<ApiError>),
+    #[error("{0} {1}: {2}")]
+    Entry(&'static str, usize, Box<ApiError>),
+    #[error("{0}")]
+    Rpc(String),
+    #[error("{0}")]
+    Unavailable(String),
+    #[error("Missing or invalid API key")]
+    Unauthorized,
+    #[error("Rate limit exceeded")]
+    RateLimited,
+    #[error("{0}")]
+    Timeout(String),
+    #[error("Request body exceeds {0} bytes")]
+    PayloadTooLarge(usize),
+    #[error("Content-Type must be application/json, got {0}")]
+    UnsupportedMediaType(String),
+    #[error("Serialization error")]
+    Serialization,
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl ApiError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ApiError::Operation(_, inner) | ApiError::Entry(_, _, inner) => inner.status(),
+            ApiError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Timeout(_) => StatusCode::REQUEST_TIMEOUT,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::Serialization | ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            ApiError::MissingFields => ErrorCode::MissingFields,
+            ApiError::InvalidPubkey => ErrorCode::InvalidPubkey,
+            ApiError::OffCurve(_) => ErrorCode::InvalidSigner,
+            ApiError::ZeroAmount => ErrorCode::ZeroAmount,
+            ApiError::InvalidAmount(_) => ErrorCode::InvalidAmount,
+            ApiError::InvalidSecret | ApiError::SecretLength(_) => ErrorCode::InvalidSecret,
+            ApiError::InvalidSignature => ErrorCode::InvalidSignature,
+            ApiError::InvalidTransaction => ErrorCode::InvalidTransaction,
+            ApiError::InvalidInput(_) => ErrorCode::InvalidInput,
+            ApiError::Instruction(_) => ErrorCode::InstructionError,
+            ApiError::Operation(_, inner) | ApiError::Entry(_, _, inner) => inner.code(),
+            ApiError::Rpc(_) => ErrorCode::RpcError,
+            ApiError::Unavailable(_) => ErrorCode::ServiceUnavailable,
+            ApiError::Unauthorized => ErrorCode::Unauthorized,
+            ApiError::RateLimited => ErrorCode::RateLimited,
+            ApiError::Timeout(_) => ErrorCode::Timeout,
+            ApiError::PayloadTooLarge(_) => ErrorCode::PayloadTooLarge,
+            ApiError::UnsupportedMediaType(_) => ErrorCode::UnsupportedMediaType,
+            ApiError::Serialization | ApiError::Internal(_) => ErrorCode::InternalError,
+        }
+    }
 }
 
 fn success(data: serde_json::Value) -> (StatusCode, Json<ApiResponse>) {
     (
         StatusCode::OK,
-        Json(ApiResponse { success: true, data: Some(data), error: None })
+        Json(ApiResponse { success: true, data: Some(data), error: None, error_code: None, request_id: None })
     )
 }
 
-fn error(msg: &str) -> (StatusCode, Json<ApiResponse>) {
+fn error(err: ApiError) -> (StatusCode, Json<ApiResponse>) {
     (
-        StatusCode::BAD_REQUEST,
-        Json(ApiResponse { success: false, data: None, error: Some(msg.to_string()) })
+        err.status(),
+        Json(ApiResponse { success: false, data: None, error: Some(err.to_string()), error_code: Some(err.code()), request_id: None })
     )
 }
 
+// --- Amount Parsing ---
+
+/// JSON numbers above 2^53 lose precision in JavaScript clients, so amounts
+/// may also be supplied as decimal strings.
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum U64OrString {
+    Number(u64),
+    String(String),
+}
+
+fn parse_u64_field<'de, D: Deserializer<'de>>(value: U64OrString, field: &str) -> Result<u64, D::Error> {
+    match value {
+        U64OrString::Number(n) => Ok(n),
+        U64OrString::String(s) => s
+            .parse()
+            .map_err(|_| de::Error::custom(format!("{field} is not a valid u64"))),
+    }
+}
+
+fn deserialize_amount<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let value = U64OrString::deserialize(deserializer)
+        .map_err(|_| de::Error::custom("amount is not a valid u64"))?;
+    parse_u64_field::<D>(value, "amount")
+}
+
+fn deserialize_optional_amount<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    let value = Option::<U64OrString>::deserialize(deserializer)
+        .map_err(|_| de::Error::custom("amount is not a valid u64"))?;
+    value.map(|v| parse_u64_field::<D>(v, "amount")).transpose()
+}
+
+fn deserialize_lamports<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let value = U64OrString::deserialize(deserializer)
+        .map_err(|_| de::Error::custom("lamports is not a valid u64"))?;
+    parse_u64_field::<D>(value, "lamports")
+}
+
+fn deserialize_optional_lamports<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    let value = Option::<U64OrString>::deserialize(deserializer)
+        .map_err(|_| de::Error::custom("lamports is not a valid u64"))?;
+    value.map(|v| parse_u64_field::<D>(v, "lamports")).transpose()
+}
+
+fn deserialize_max_fee<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let value = U64OrString::deserialize(deserializer)
+        .map_err(|_| de::Error::custom("maxFee is not a valid u64"))?;
+    parse_u64_field::<D>(value, "maxFee")
+}
+
 // --- Endpoint Structs ---
 
-#[derive(Serialize)]
+#[derive(Deserialize, JsonSchema)]
+struct GenerateKeypairQuery {
+    #[serde(rename = "asBytes", default)]
+    as_bytes: bool,
+    /// `base58` (default), `base64` or `hex` for the returned secret.
+    encoding: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
 struct KeypairResponse {
     pubkey: String,
     secret: String,
+    /// The 64-byte array format used by Solana CLI keypair files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes: Option<Vec<u8>>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ImportKeypairRequest {
+    secret: Option<String>,
+    bytes: Option<Vec<u8>>,
+    /// `base58` (default), `base64` or `hex` for the returned secret.
+    encoding: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SeedKeypairRequest {
+    /// 32-byte ed25519 seed.
+    seed: String,
+    /// `base58` or `base64`; detected from the value when omitted.
+    #[serde(rename = "seedEncoding")]
+    seed_encoding: Option<String>,
+    /// `base58` (default), `base64` or `hex` for the returned secret.
+    encoding: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct KeypairBatchRequest {
+    count: usize,
+    /// `base58` (default), `base64` or `hex` for the returned secret.
+    encoding: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct KeypairBatchResponse {
+    keypairs: Vec<KeypairResponse>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct MnemonicKeypairRequest {
+    mnemonic: String,
+    passphrase: Option<String>,
+    #[serde(rename = "derivationPath")]
+    derivation_path: Option<String>,
+    /// `base58` (default), `base64` or `hex` for the returned secret.
+    encoding: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct MnemonicKeypairResponse {
+    pubkey: String,
+    secret: String,
+    #[serde(rename = "derivationPath")]
+    derivation_path: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct VanityKeypairRequest {
+    prefix: String,
+    #[serde(rename = "caseInsensitive", default)]
+    case_insensitive: bool,
+    #[serde(rename = "timeoutMs")]
+    timeout_ms: Option<u64>,
+    /// `base58` (default), `base64` or `hex` for the returned secret.
+    encoding: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct VanityKeypairResponse {
+    pubkey: String,
+    secret: String,
+    attempts: u64,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 struct CreateTokenRequest {
     #[serde(rename = "mintAuthority")]
     mint_authority: String,
     mint: String,
     decimals: u8,
+    #[serde(rename = "freezeAuthority")]
+    freeze_authority: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Deserialize, JsonSchema)]
+struct EncodingQuery {
+    encoding: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
 struct AccountMetaCamel {
     pubkey: String,
     #[serde(rename = "isSigner")]
@@ -63,305 +359,6211 @@ struct AccountMetaCamel {
     is_writable: Option<bool>,
 }
 
-#[derive(Serialize)]
-struct InstructionResponseCreateToken {
+#[derive(Serialize, JsonSchema)]
+struct InstructionResponse {
+    #[serde(rename = "programId")]
     program_id: String,
-    accounts: serde_json::Map<String, serde_json::Value>,
+    accounts: Vec<AccountMetaCamel>,
+    #[serde(rename = "instructionData")]
     instruction_data: String,
 }
 
-#[derive(Serialize)]
-struct InstructionResponseMintToken {
-    program_id: String,
-    accounts: Vec<AccountMetaCamel>,
-    instruction_data: String,
+#[derive(Serialize, JsonSchema)]
+struct InstructionsResponse {
+    instructions: Vec<InstructionResponse>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct InstructionResponseSendSol {
+    #[serde(rename = "programId")]
     program_id: String,
     accounts: Vec<String>,
+    #[serde(rename = "instructionData")]
     instruction_data: String,
+    lamports: u64,
+    sol: String,
 }
 
-#[derive(Serialize)]
-struct InstructionResponseSendToken {
+#[derive(Serialize, JsonSchema)]
+struct InstructionResponseSendSolWithSeed {
+    #[serde(rename = "programId")]
     program_id: String,
-    accounts: Vec<AccountMetaCamel>,
+    accounts: Vec<String>,
+    /// Whether the account at the same index in `accounts` must sign.
+    #[serde(rename = "isSigner")]
+    is_signer: Vec<bool>,
+    #[serde(rename = "instructionData")]
     instruction_data: String,
+    lamports: u64,
+    sol: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct CreateMintFullRequest {
+    payer: String,
+    /// The `/token/create` fields: `mint`, `mintAuthority`, `decimals` and `freezeAuthority`.
+    #[serde(flatten)]
+    mint: CreateTokenRequest,
+    /// Overrides the rent-exempt balance computed from the default rent parameters.
+    #[serde(default, deserialize_with = "deserialize_optional_lamports")]
+    #[schemars(with = "Option<U64OrString>")]
+    lamports: Option<u64>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct InitializeAccountRequest {
+    account: String,
+    mint: String,
+    owner: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct CreateTokenAccountFullRequest {
+    payer: String,
+    account: String,
+    mint: String,
+    owner: String,
+    /// Overrides the rent-exempt balance computed from the default rent parameters.
+    #[serde(default, deserialize_with = "deserialize_optional_lamports")]
+    #[schemars(with = "Option<U64OrString>")]
+    lamports: Option<u64>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 struct MintTokenRequest {
     mint: String,
     destination: String,
     authority: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    #[schemars(with = "U64OrString")]
+    amount: u64,
+    #[serde(default)]
+    signers: Vec<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct MintTokenCheckedRequest {
+    mint: String,
+    destination: String,
+    authority: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    #[schemars(with = "U64OrString")]
     amount: u64,
+    decimals: u8,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 struct SignMessageRequest {
     message: String,
     secret: String,
+    /// `base64` (default), `base58` or `hex` for the `signature` field.
+    encoding: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct SignMessageResponse {
+    /// Base64 unless `encoding` says otherwise, kept for existing clients.
     signature: String,
+    #[serde(rename = "signatureBase58")]
+    signature_base58: String,
+    #[serde(rename = "signatureBase64")]
+    signature_base64: String,
     public_key: String,
     message: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
+struct SignMessageBatchRequest {
+    secret: String,
+    messages: Vec<String>,
+    /// `base64` (default), `base58` or `hex` for the signatures.
+    encoding: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct SignMessageBatchResponse {
+    #[serde(rename = "publicKey")]
+    public_key: String,
+    /// One signature per input message, in the same order.
+    signatures: Vec<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
 struct VerifyMessageRequest {
     message: String,
     signature: String,
     pubkey: String,
 }
 
-#[derive(Serialize)]
+#[derive(Deserialize, JsonSchema)]
+struct VerifyMessageBatchRequest {
+    items: Vec<VerifyMessageRequest>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct VerifyResult {
+    valid: bool,
+    /// Why the item could not be checked; absent for well-formed items.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct VerifyMessageBatchResponse {
+    /// One result per input item, in the same order.
+    results: Vec<VerifyResult>,
+}
+
+#[derive(Serialize, JsonSchema)]
 struct VerifyMessageResponse {
     valid: bool,
     message: String,
     pubkey: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
+struct VerifyMessageMultiRequest {
+    message: String,
+    signature: String,
+    pubkeys: Vec<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct VerifyMessageMultiResponse {
+    valid: bool,
+    #[serde(rename = "matchedPubkey")]
+    matched_pubkey: Option<String>,
+}
+
+/// Fields of a Sign-In With Solana message, in their canonical order.
+#[derive(Serialize, Deserialize, JsonSchema, Default)]
+struct SiwsFields {
+    domain: String,
+    address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    statement: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(rename = "chainId", skip_serializing_if = "Option::is_none")]
+    chain_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nonce: Option<String>,
+    #[serde(rename = "issuedAt", skip_serializing_if = "Option::is_none")]
+    issued_at: Option<String>,
+    #[serde(rename = "expirationTime", skip_serializing_if = "Option::is_none")]
+    expiration_time: Option<String>,
+    #[serde(rename = "notBefore", skip_serializing_if = "Option::is_none")]
+    not_before: Option<String>,
+    #[serde(rename = "requestId", skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    resources: Vec<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct SiwsBuildResponse {
+    message: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SiwsVerifyRequest {
+    message: String,
+    signature: String,
+    address: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct SiwsVerifyResponse {
+    valid: bool,
+    fields: SiwsFields,
+}
+
+#[derive(Deserialize, JsonSchema)]
 struct SendSolRequest {
     from: String,
     to: String,
+    #[serde(default, deserialize_with = "deserialize_optional_lamports")]
+    #[schemars(with = "Option<U64OrString>")]
+    lamports: Option<u64>,
+    sol: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SendSolWithSeedRequest {
+    /// Address derived from `fromBase`, `seed` and `fromOwner`.
+    #[serde(rename = "fromPubkey")]
+    from_pubkey: String,
+    #[serde(rename = "fromBase")]
+    from_base: String,
+    seed: String,
+    #[serde(rename = "fromOwner")]
+    from_owner: String,
+    to: String,
+    #[serde(deserialize_with = "deserialize_lamports")]
+    #[schemars(with = "U64OrString")]
     lamports: u64,
 }
 
-#[derive(Deserialize)]
-struct SendTokenRequest {
+#[derive(Deserialize, JsonSchema)]
+struct SolRecipient {
+    to: String,
+    #[serde(deserialize_with = "deserialize_lamports")]
+    #[schemars(with = "U64OrString")]
+    lamports: u64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SendSolMultiRequest {
+    from: String,
+    recipients: Vec<SolRecipient>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct MultiTransferResponse {
+    instructions: Vec<InstructionResponse>,
+    /// Size of a single transaction carrying every instruction, signatures included.
+    #[serde(rename = "estimatedTxSize")]
+    estimated_tx_size: usize,
+    /// Set when the instructions won't fit in one transaction and must be split.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct TokenTransferEntry {
+    /// Destination token account.
+    destination: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    #[schemars(with = "U64OrString")]
+    amount: u64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SendTokenMultiRequest {
+    owner: String,
+    mint: String,
+    decimals: u8,
+    transfers: Vec<TokenTransferEntry>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SendTokenCheckedRequest {
+    source: String,
     destination: String,
     mint: String,
     owner: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    #[schemars(with = "U64OrString")]
     amount: u64,
+    decimals: u8,
 }
 
-// --- Endpoints ---
+#[derive(Deserialize, JsonSchema)]
+struct BurnTokenRequest {
+    account: String,
+    mint: String,
+    owner: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    #[schemars(with = "U64OrString")]
+    amount: u64,
+}
 
-#[handler]
-async fn generate_keypair() -> (StatusCode, Json<ApiResponse>) {
-    let keypair = Keypair::new();
-    let resp = KeypairResponse {
-        pubkey: keypair.pubkey().to_string(),
-        secret: keypair.to_bytes().as_ref().to_base58(),
-    };
-    match serde_json::to_value(resp) {
-        Ok(val) => success(val),
-        Err(_) => error("Serialization error"),
-    }
+#[derive(Deserialize, JsonSchema)]
+struct BurnTokenCheckedRequest {
+    account: String,
+    mint: String,
+    owner: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    #[schemars(with = "U64OrString")]
+    amount: u64,
+    decimals: u8,
 }
 
-#[handler]
-async fn create_token(Json(req): Json<CreateTokenRequest>) -> (StatusCode, Json<ApiResponse>) {
-    let mint_authority = Pubkey::from_str(&req.mint_authority);
-    let mint = Pubkey::from_str(&req.mint);
-    if mint_authority.is_err() || mint.is_err() {
-        return error("Invalid public key(s)");
-    }
-    let instruction = token_instruction::initialize_mint(
-        &spl_token::id(),
-        &mint.unwrap(),
-        &mint_authority.unwrap(),
-        None,
-        req.decimals,
-    );
-    match instruction {
-        Ok(ix) => {
-            let mut accounts_map = serde_json::Map::new();
-            for meta in ix.accounts.iter() {
-                let mut obj = serde_json::Map::new();
-                obj.insert("pubkey".to_string(), serde_json::Value::String(meta.pubkey.to_string()));
-                obj.insert("isSigner".to_string(), serde_json::Value::Bool(meta.is_signer));
-                obj.insert("isWritable".to_string(), serde_json::Value::Bool(meta.is_writable));
-                accounts_map.insert(meta.pubkey.to_string(), serde_json::Value::Object(obj));
-            }
-            let resp = InstructionResponseCreateToken {
-                program_id: ix.program_id.to_string(),
-                accounts: accounts_map,
-                instruction_data: general_purpose::STANDARD.encode(&ix.data),
-            };
-            match serde_json::to_value(resp) {
-                Ok(val) => success(val),
-                Err(_) => error("Serialization error"),
-            }
-        }
-        Err(e) => error(&format!("Failed to create instruction: {e}")),
-    }
+#[derive(Deserialize, JsonSchema)]
+struct CloseAccountRequest {
+    account: String,
+    destination: String,
+    owner: String,
 }
 
-#[handler]
-async fn mint_token(Json(req): Json<MintTokenRequest>) -> (StatusCode, Json<ApiResponse>) {
-    let mint = Pubkey::from_str(&req.mint);
-    let destination = Pubkey::from_str(&req.destination);
-    let authority = Pubkey::from_str(&req.authority);
-    if mint.is_err() || destination.is_err() || authority.is_err() {
-        return error("Invalid public key(s)");
-    }
-    let instruction = token_instruction::mint_to(
-        &spl_token::id(),
-        &mint.unwrap(),
-        &destination.unwrap(),
-        &authority.unwrap(),
-        &[],
-        req.amount,
-    );
-    match instruction {
-        Ok(ix) => {
-            let accounts = ix.accounts.iter().map(|meta| AccountMetaCamel {
-                pubkey: meta.pubkey.to_string(),
-                is_signer: meta.is_signer,
-                is_writable: Some(meta.is_writable),
-            }).collect();
-            let resp = InstructionResponseMintToken {
-                program_id: ix.program_id.to_string(),
-                accounts,
-                instruction_data: general_purpose::STANDARD.encode(&ix.data),
-            };
-            match serde_json::to_value(resp) {
-                Ok(val) => success(val),
-                Err(_) => error("Serialization error"),
-            }
-        }
-        Err(e) => error(&format!("Failed to create instruction: {e}")),
-    }
+#[derive(Deserialize, JsonSchema)]
+struct ApproveRequest {
+    account: String,
+    delegate: String,
+    owner: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    #[schemars(with = "U64OrString")]
+    amount: u64,
 }
 
-#[handler]
-async fn sign_message(Json(req): Json<SignMessageRequest>) -> (StatusCode, Json<ApiResponse>) {
-    if req.message.is_empty() || req.secret.is_empty() {
-        return error("Missing required fields");
-    }
-    let secret_bytes = req.secret.from_base58();
-    if let Ok(bytes) = secret_bytes {
-        if let Ok(keypair) = Keypair::from_bytes(&bytes) {
-            let signature = keypair.sign_message(req.message.as_bytes());
-            let resp = SignMessageResponse {
-                signature: general_purpose::STANDARD.encode(signature.as_ref()),
-                public_key: keypair.pubkey().to_string(),
-                message: req.message,
-            };
-            return match serde_json::to_value(resp) {
-                Ok(val) => success(val),
-                Err(_) => error("Serialization error"),
-            };
-        }
-    }
-    error("Invalid secret key")
+#[derive(Deserialize, JsonSchema)]
+struct RevokeRequest {
+    account: String,
+    owner: String,
 }
 
-#[handler]
-async fn verify_message(Json(req): Json<VerifyMessageRequest>) -> (StatusCode, Json<ApiResponse>) {
-    if req.message.is_empty() || req.signature.is_empty() || req.pubkey.is_empty() {
-        return error("Missing required fields");
-    }
-    let pubkey = Pubkey::from_str(&req.pubkey);
-    let signature_bytes = general_purpose::STANDARD.decode(&req.signature);
-    if let (Ok(pubkey), Ok(sig_bytes)) = (pubkey, signature_bytes) {
-        let signature = Signature::new(&sig_bytes);
-        let valid = signature.verify(&pubkey.to_bytes(), req.message.as_bytes());
-        let resp = VerifyMessageResponse {
-            valid,
-            message: req.message,
-            pubkey: req.pubkey,
-        };
-        return match serde_json::to_value(resp) {
-            Ok(val) => success(val),
-            Err(_) => error("Serialization error"),
-        };
-    }
-    error("Invalid signature or public key")
+#[derive(Deserialize, JsonSchema)]
+struct FreezeAccountRequest {
+    account: String,
+    mint: String,
+    authority: String,
 }
 
-#[handler]
-async fn send_sol(Json(req): Json<SendSolRequest>) -> (StatusCode, Json<ApiResponse>) {
-    let from = Pubkey::from_str(&req.from);
-    let to = Pubkey::from_str(&req.to);
-    if from.is_err() || to.is_err() {
-        return error("Invalid public key(s)");
-    }
-    if req.lamports == 0 {
-        return error("Amount must be greater than zero");
-    }
-    let ix = system_instruction::transfer(&from.unwrap(), &to.unwrap(), req.lamports);
-    let accounts = ix.accounts.iter().map(|meta| meta.pubkey.to_string()).collect();
-    let resp = InstructionResponseSendSol {
-        program_id: ix.program_id.to_string(),
-        accounts,
-        instruction_data: general_purpose::STANDARD.encode(&ix.data),
-    };
-    match serde_json::to_value(resp) {
-        Ok(val) => success(val),
-        Err(_) => error("Serialization error"),
-    }
+#[derive(Deserialize, JsonSchema)]
+struct SetAuthorityRequest {
+    account: String,
+    current_authority: String,
+    new_authority: Option<String>,
+    authority_type: String,
 }
 
-#[handler]
-async fn send_token(Json(req): Json<SendTokenRequest>) -> (StatusCode, Json<ApiResponse>) {
-    let destination = Pubkey::from_str(&req.destination);
-    let mint = Pubkey::from_str(&req.mint);
-    let owner = Pubkey::from_str(&req.owner);
-    if destination.is_err() || mint.is_err() || owner.is_err() {
-        return error("Invalid public key(s)");
-    }
-    if req.amount == 0 {
-        return error("Amount must be greater than zero");
-    }
-    let destination = destination.unwrap();
-    let ix = token_instruction::transfer(
-        &spl_token::id(),
-        &destination,
-        &destination, // source and destination are the same for simplicity
-        &owner.unwrap(),
-        &[],
-        req.amount,
-    );
-    match ix {
-        Ok(ix) => {
-            let accounts = ix.accounts.iter().map(|meta| AccountMetaCamel {
-                pubkey: meta.pubkey.to_string(),
-                is_signer: meta.is_signer,
-                is_writable: None,
-            }).collect();
-            let resp = InstructionResponseSendToken {
-                program_id: ix.program_id.to_string(),
-                accounts,
-                instruction_data: general_purpose::STANDARD.encode(&ix.data),
-            };
-            match serde_json::to_value(resp) {
-                Ok(val) => success(val),
-                Err(_) => error("Serialization error"),
-            }
-        }
-        Err(e) => error(&format!("Failed to create instruction: {e}")),
-    }
+#[derive(Deserialize, JsonSchema)]
+struct CreateNonceRequest {
+    payer: String,
+    #[serde(rename = "nonceAccount")]
+    nonce_account: String,
+    authority: String,
+    /// Defaults to the rent-exempt minimum for a nonce account.
+    #[serde(default, deserialize_with = "deserialize_optional_lamports")]
+    #[schemars(with = "Option<U64OrString>")]
+    lamports: Option<u64>,
 }
 
-#[handler]
-async fn health() -> (StatusCode, Json<ApiResponse>) {
-    success(serde_json::json!({"status": "OK"}))
+#[derive(Deserialize, JsonSchema)]
+struct AdvanceNonceRequest {
+    #[serde(rename = "nonceAccount")]
+    nonce_account: String,
+    authority: String,
 }
 
-#[tokio::main]
-async fn main() -> Result<(), std::io::Error> {
-    dotenv().ok();
-    let port = env::var("PORT").unwrap_or_else(|_| "3000".to_string());
-    let addr = format!("0.0.0.0:{}", port);
-    let app = Route::new()
-        .at("/health", health)
-        .at("/keypair", generate_keypair)
-        .at("/token/create", create_token)
+#[derive(Deserialize, JsonSchema)]
+struct WithdrawNonceRequest {
+    #[serde(rename = "nonceAccount")]
+    nonce_account: String,
+    authority: String,
+    to: String,
+    #[serde(deserialize_with = "deserialize_lamports")]
+    #[schemars(with = "U64OrString")]
+    lamports: u64,
+}
+
+/// Stake lockup; all fields default to "no lockup".
+#[derive(Deserialize, JsonSchema, Default)]
+struct LockupInput {
+    #[serde(rename = "unixTimestamp", default)]
+    unix_timestamp: i64,
+    #[serde(default)]
+    epoch: u64,
+    custodian: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct CreateStakeRequest {
+    payer: String,
+    #[serde(rename = "stakeAccount")]
+    stake_account: String,
+    staker: String,
+    withdrawer: String,
+    #[serde(deserialize_with = "deserialize_lamports")]
+    #[schemars(with = "U64OrString")]
+    lamports: u64,
+    #[serde(default)]
+    lockup: LockupInput,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct DelegateStakeRequest {
+    #[serde(rename = "stakeAccount")]
+    stake_account: String,
+    /// The stake authority.
+    authority: String,
+    #[serde(rename = "voteAccount")]
+    vote_account: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct DeactivateStakeRequest {
+    #[serde(rename = "stakeAccount")]
+    stake_account: String,
+    /// The stake authority.
+    authority: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct WithdrawStakeRequest {
+    #[serde(rename = "stakeAccount")]
+    stake_account: String,
+    /// The withdraw authority.
+    authority: String,
+    destination: String,
+    #[serde(deserialize_with = "deserialize_lamports")]
+    #[schemars(with = "U64OrString")]
+    lamports: u64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct CreateLookupTableRequest {
+    authority: String,
+    payer: String,
+    /// A recent slot, used to derive the table address.
+    #[serde(rename = "recentSlot")]
+    recent_slot: u64,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct CreateLookupTableResponse {
+    instructions: Vec<InstructionResponse>,
+    #[serde(rename = "tableAddress")]
+    table_address: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ExtendLookupTableRequest {
+    table: String,
+    authority: String,
+    /// Funds the rent for the added addresses; may be omitted if the table already holds enough.
+    payer: Option<String>,
+    addresses: Vec<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct InitializeMultisigRequest {
+    multisig: String,
+    signers: Vec<String>,
+    /// Number of signatures required to authorize an operation.
+    m: u8,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct CreateAccountRequest {
+    payer: String,
+    #[serde(rename = "newAccount")]
+    new_account: String,
+    #[serde(deserialize_with = "deserialize_lamports")]
+    #[schemars(with = "U64OrString")]
+    lamports: u64,
+    space: u64,
+    owner: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct AssignRequest {
+    account: String,
+    owner: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct AllocateRequest {
+    account: String,
+    space: u64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct AccountMetaInput {
+    pubkey: String,
+    #[serde(rename = "isSigner", default)]
+    is_signer: bool,
+    #[serde(rename = "isWritable", default)]
+    is_writable: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct InstructionInput {
+    #[serde(rename = "programId", alias = "program_id")]
+    program_id: String,
+    accounts: Vec<AccountMetaInput>,
+    #[serde(rename = "instructionData", alias = "instruction_data")]
+    instruction_data: String,
+}
+
+/// An address lookup table and its current contents, used to compile v0 messages.
+#[derive(Deserialize, JsonSchema)]
+struct LookupTableInput {
+    address: String,
+    addresses: Vec<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct BuildTxRequest {
+    instructions: Vec<InstructionInput>,
+    #[serde(rename = "feePayer")]
+    fee_payer: String,
+    /// Defaults to the server's cached blockhash.
+    #[serde(rename = "recentBlockhash")]
+    recent_blockhash: Option<String>,
+    /// `"legacy"` (default) or `"0"`.
+    version: Option<String>,
+    #[serde(rename = "lookupTables", default)]
+    lookup_tables: Vec<LookupTableInput>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct LookupResponse {
+    address: String,
+    #[serde(rename = "writableIndexes")]
+    writable_indexes: Vec<u8>,
+    #[serde(rename = "readonlyIndexes")]
+    readonly_indexes: Vec<u8>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct BuildTxResponse {
+    message: String,
+    #[serde(rename = "recentBlockhash")]
+    recent_blockhash: String,
+    signers: Vec<String>,
+    version: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    lookups: Vec<LookupResponse>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct MessageBytesRequest {
+    instructions: Vec<InstructionInput>,
+    #[serde(rename = "feePayer")]
+    fee_payer: String,
+    #[serde(rename = "recentBlockhash")]
+    recent_blockhash: String,
+    /// `"legacy"` (default) or `"0"`.
+    version: Option<String>,
+    #[serde(rename = "lookupTables", default)]
+    lookup_tables: Vec<LookupTableInput>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct MessageBytesResponse {
+    /// Base64 of the exact bytes each signer signs.
+    #[serde(rename = "messageBytes")]
+    message_bytes: String,
+    /// Hex SHA-256 digest of the message bytes, for signers that display it.
+    sha256: String,
+    signers: Vec<String>,
+    version: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct TxSizeRequest {
+    instructions: Vec<InstructionInput>,
+    /// Defaults to the first signer across the instructions.
+    #[serde(rename = "feePayer")]
+    fee_payer: Option<String>,
+    /// `"legacy"` (default) or `"0"`.
+    version: Option<String>,
+    #[serde(rename = "lookupTables", default)]
+    lookup_tables: Vec<LookupTableInput>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct TxSizeResponse {
+    /// Serialized transaction size in bytes, including signatures.
+    size: usize,
+    #[serde(rename = "messageSize")]
+    message_size: usize,
+    #[serde(rename = "numSignatures")]
+    num_signatures: usize,
+    limit: usize,
+    #[serde(rename = "fitsInSinglePacket")]
+    fits_in_single_packet: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct TxSignersRequest {
+    instructions: Vec<InstructionInput>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct TxSignersResponse {
+    /// Every signer across the instructions, deduplicated in first-seen order.
+    signers: Vec<String>,
+    /// The first signer, which pays fees unless another fee payer is chosen.
+    #[serde(rename = "feePayer")]
+    fee_payer: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SignTxRequest {
+    /// Base64-encoded legacy or v0 message, as returned by `/tx/build`.
+    message: String,
+    secrets: Vec<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct SignerSignature {
+    pubkey: String,
+    signature: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct SignTxResponse {
+    transaction: String,
+    signatures: Vec<SignerSignature>,
+    complete: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ExternalSignature {
+    pubkey: String,
+    /// 64-byte signature over the message bytes, base58 or base64.
+    signature: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct AttachSignaturesRequest {
+    /// Base64-encoded legacy or v0 message, as returned by `/tx/message-bytes`.
+    message: String,
+    signatures: Vec<ExternalSignature>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct AttachSignaturesResponse {
+    transaction: String,
+    signatures: Vec<SignerSignature>,
+    complete: bool,
+    #[serde(rename = "missingSigners")]
+    missing_signers: Vec<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SendTxRequest {
+    transaction: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct SendTxResponse {
+    signature: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct SimulateTxResponse {
+    logs: Vec<String>,
+    #[serde(rename = "unitsConsumed")]
+    units_consumed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    err: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct DecodedInstruction {
+    #[serde(rename = "programId")]
+    program_id: String,
+    /// `null` entries are loaded from an address lookup table and can't be resolved offline.
+    accounts: Vec<Option<String>>,
+    #[serde(rename = "instructionData")]
+    instruction_data: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct DecodeTxResponse {
+    version: String,
+    #[serde(rename = "feePayer")]
+    fee_payer: Option<String>,
+    #[serde(rename = "recentBlockhash")]
+    recent_blockhash: String,
+    signatures: Vec<String>,
+    instructions: Vec<DecodedInstruction>,
+    #[serde(rename = "addressTableLookups", skip_serializing_if = "Vec::is_empty")]
+    address_table_lookups: Vec<LookupResponse>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct DecodeTokenInstructionRequest {
+    #[serde(rename = "programId")]
+    program_id: String,
+    /// Base64-encoded instruction data.
+    #[serde(rename = "instructionData")]
+    instruction_data: String,
+    #[serde(default)]
+    accounts: Vec<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct DecodeTokenInstructionResponse {
+    /// The `TokenInstruction` variant name, e.g. `TransferChecked`.
+    #[serde(rename = "instructionType")]
+    instruction_type: String,
+    /// Variant fields in camelCase; pubkeys as base58, absent authorities as `null`.
+    params: serde_json::Value,
+    accounts: Vec<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct CommitmentQuery {
+    /// `processed`, `confirmed` or `finalized`; defaults to the server's `COMMITMENT`.
+    commitment: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct FreshQuery {
+    /// Bypass the blockhash cache and fetch from the cluster.
+    #[serde(default)]
+    fresh: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct BlockhashQuery {
+    /// `processed`, `confirmed` or `finalized`; defaults to the server's `COMMITMENT`.
+    commitment: Option<String>,
+    /// Bypass the blockhash cache and fetch from the cluster.
+    #[serde(default)]
+    fresh: bool,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct BlockhashResponse {
+    blockhash: String,
+    #[serde(rename = "lastValidBlockHeight")]
+    last_valid_block_height: u64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct FeeRequest {
+    /// Base64-encoded legacy or v0 message, as returned by `/tx/build`.
+    message: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct FeeResponse {
+    lamports: u64,
+    sol: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct RentRequest {
+    /// Account data size in bytes. Omit to get the mint and token account minimums.
+    space: Option<usize>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct RentExemption {
+    space: usize,
+    lamports: u64,
+    sol: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(untagged)]
+enum RentResponse {
+    Sized(RentExemption),
+    Common {
+        mint: RentExemption,
+        #[serde(rename = "tokenAccount")]
+        token_account: RentExemption,
+    },
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ConfirmTxRequest {
+    signature: String,
+    /// `processed`, `confirmed` or `finalized`; defaults to the server's `COMMITMENT`.
+    commitment: Option<String>,
+    /// How long to poll; capped by the server's `CONFIRM_MAX_TIMEOUT_MS`.
+    #[serde(rename = "timeoutMs")]
+    timeout_ms: Option<u64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ConfirmTxResponse {
+    confirmed: bool,
+    /// Last confirmation status seen, if the cluster knows the signature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    err: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct BalanceResponse {
+    pubkey: String,
+    lamports: u64,
+    sol: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct TokenBalanceResponse {
+    ata: String,
+    amount: String,
+    #[serde(rename = "uiAmount", skip_serializing_if = "Option::is_none")]
+    ui_amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decimals: Option<u8>,
+    exists: bool,
+}
+
+/// A PDA seed tagged with its encoding, e.g. `{ "type": "utf8", "value": "state" }`,
+/// `{ "type": "base64", "value": "AQID" }` or `{ "type": "base58", "value": "<pubkey>" }`.
+#[derive(Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+enum SeedInput {
+    Utf8(String),
+    Base64(String),
+    Base58(String),
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct FindPdaRequest {
+    #[serde(rename = "programId")]
+    program_id: String,
+    seeds: Vec<SeedInput>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct FindPdaResponse {
+    address: String,
+    bump: u8,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ValidatePubkeyRequest {
+    pubkey: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ValidatePubkeyResponse {
+    valid: bool,
+    #[serde(rename = "onCurve")]
+    on_curve: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct MemoRequest {
+    memo: String,
+    #[serde(default)]
+    signers: Vec<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ComputeUnitLimitRequest {
+    units: u32,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ComputeUnitPriceRequest {
+    #[serde(rename = "microLamports")]
+    micro_lamports: u64,
+}
+
+/// A single `/batch` operation, e.g. `{ "type": "sendSol", "params": { ... } }`,
+/// where `params` matches the body of the corresponding endpoint.
+#[derive(Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "params", rename_all = "camelCase")]
+enum BatchOperation {
+    CreateToken(CreateTokenRequest),
+    CreateMintFull(CreateMintFullRequest),
+    MintToken(MintTokenRequest),
+    MintTokenChecked(MintTokenCheckedRequest),
+    SendSol(SendSolRequest),
+    SendToken(SendTokenRequest),
+    SendTokenChecked(SendTokenCheckedRequest),
+    BurnToken(BurnTokenRequest),
+    BurnTokenChecked(BurnTokenCheckedRequest),
+    CloseAccount(CloseAccountRequest),
+    Approve(ApproveRequest),
+    Revoke(RevokeRequest),
+    FreezeAccount(FreezeAccountRequest),
+    ThawAccount(FreezeAccountRequest),
+    SetAuthority(SetAuthorityRequest),
+    CreateTokenAccountFull(CreateTokenAccountFullRequest),
+    InitializeMultisig(InitializeMultisigRequest),
+    CreateAccount(CreateAccountRequest),
+    CreateAta(CreateAtaRequest),
+    Memo(MemoRequest),
+    ComputeUnitLimit(ComputeUnitLimitRequest),
+    ComputeUnitPrice(ComputeUnitPriceRequest),
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct BatchRequest {
+    #[schemars(with = "Vec<BatchOperation>")]
+    operations: Vec<serde_json::Value>,
+}
+
+/// A single value or a list, for fields that may repeat.
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            OneOrMany::One(value) => vec![value],
+            OneOrMany::Many(values) => values,
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SolanaPayTransferRequest {
+    recipient: String,
+    /// Decimal amount in SOL, or in tokens when `splToken` is set, e.g. `"1.5"`.
+    amount: Option<String>,
+    #[serde(rename = "splToken")]
+    spl_token: Option<String>,
+    /// One reference pubkey or a list of them.
+    reference: Option<OneOrMany>,
+    label: Option<String>,
+    message: Option<String>,
+    memo: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct SolanaPayUrlResponse {
+    url: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SolanaPayParseRequest {
+    url: String,
+}
+
+#[derive(Serialize, JsonSchema, Default)]
+struct SolanaPayParseResponse {
+    /// `"transfer"` or `"transaction"`.
+    #[serde(rename = "requestType")]
+    request_type: String,
+    /// The HTTPS endpoint of a transaction request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    link: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recipient: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amount: Option<String>,
+    #[serde(rename = "splToken", skip_serializing_if = "Option::is_none")]
+    spl_token: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    reference: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct DeriveAtaRequest {
+    owner: String,
+    mint: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct DeriveAtaResponse {
+    ata: String,
+    owner: String,
+    mint: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct TransferFeeRequest {
+    #[serde(deserialize_with = "deserialize_amount")]
+    #[schemars(with = "U64OrString")]
+    amount: u64,
+    #[serde(rename = "feeBasisPoints")]
+    fee_basis_points: u16,
+    #[serde(rename = "maxFee", deserialize_with = "deserialize_max_fee")]
+    #[schemars(with = "U64OrString")]
+    max_fee: u64,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct TransferFeeResponse {
+    fee: u64,
+    net: u64,
+}
+
+/// Exactly one of `amount` and `uiAmount` must be set.
+#[derive(Deserialize, JsonSchema)]
+struct ConvertAmountRequest {
+    /// Raw base-unit amount to render as a UI amount.
+    #[serde(default, deserialize_with = "deserialize_optional_amount")]
+    #[schemars(with = "Option<U64OrString>")]
+    amount: Option<u64>,
+    /// Decimal string such as `"1.5"` to convert to base units.
+    #[serde(rename = "uiAmount")]
+    ui_amount: Option<String>,
+    decimals: u8,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ConvertAmountResponse {
+    amount: String,
+    #[serde(rename = "uiAmount")]
+    ui_amount: String,
+    decimals: u8,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct CreateAtaRequest {
+    funder: String,
+    owner: String,
+    mint: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct WrapSolRequest {
+    owner: String,
+    #[serde(deserialize_with = "deserialize_lamports")]
+    #[schemars(with = "U64OrString")]
+    lamports: u64,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct UnwrapSolRequest {
+    owner: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct InstructionResponseCreateAta {
+    #[serde(flatten)]
+    instruction: InstructionResponse,
+    ata: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SendTokenRequest {
+    source: String,
+    destination: String,
+    mint: String,
+    owner: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    #[schemars(with = "U64OrString")]
+    amount: u64,
+}
+
+fn parse_authority_type(value: &str) -> Option<AuthorityType> {
+    match value {
+        "MintTokens" => Some(AuthorityType::MintTokens),
+        "FreezeAccount" => Some(AuthorityType::FreezeAccount),
+        "AccountOwner" => Some(AuthorityType::AccountOwner),
+        "CloseAccount" => Some(AuthorityType::CloseAccount),
+        _ => None,
+    }
+}
+
+fn parse_sol_amount(value: &str) -> Result<u64, ApiError> {
+    let (whole, frac) = value.split_once('.').unwrap_or((value, ""));
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && frac.is_empty()) || !is_digits(whole) || !is_digits(frac) {
+        return Err(ApiError::InvalidAmount("Invalid sol amount".to_string()));
+    }
+    if frac.len() > 9 {
+        return Err(ApiError::InvalidAmount("sol amount supports at most 9 decimal places".to_string()));
+    }
+    let whole: u64 = if whole.is_empty() { 0 } else {
+        whole.parse().map_err(|_| ApiError::InvalidAmount("sol amount is too large".to_string()))?
+    };
+    let frac: u64 = format!("{frac:0<9}").parse().map_err(|_| ApiError::InvalidAmount("Invalid sol amount".to_string()))?;
+    whole
+        .checked_mul(LAMPORTS_PER_SOL)
+        .and_then(|lamports| lamports.checked_add(frac))
+        .ok_or_else(|| ApiError::InvalidAmount("sol amount is too large".to_string()))
+}
+
+/// Checks a Solana Pay amount: a non-negative decimal with a leading digit.
+/// SOL amounts are limited to 9 decimal places; token precision depends on the mint.
+fn validate_pay_amount(value: &str, is_sol: bool) -> Result<(), ApiError> {
+    if value.starts_with('-') {
+        return Err(ApiError::InvalidAmount("amount must not be negative".to_string()));
+    }
+    let (whole, frac) = value.split_once('.').unwrap_or((value, ""));
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if whole.is_empty() || !is_digits(whole) || !is_digits(frac) || value.ends_with('.') {
+        return Err(ApiError::InvalidAmount(format!("Invalid amount '{value}'")));
+    }
+    if is_sol {
+        parse_sol_amount(value)?;
+    }
+    Ok(())
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters.
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?)
+                .ok()
+                .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()))?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+const SOLANA_PAY_SCHEME: &str = "solana:";
+
+/// Splits a `solana:` URL into a transfer request (pubkey recipient plus
+/// query parameters) or a transaction request (an HTTPS link).
+fn parse_solana_pay_url(url: &str) -> Result<SolanaPayParseResponse, ApiError> {
+    let invalid = |reason: String| ApiError::InvalidInput(format!("Invalid Solana Pay URL: {reason}"));
+    let rest = url
+        .strip_prefix(SOLANA_PAY_SCHEME)
+        .ok_or_else(|| invalid(format!("scheme must be '{SOLANA_PAY_SCHEME}'")))?;
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    if path.is_empty() {
+        return Err(invalid("missing recipient".to_string()));
+    }
+    let Ok(recipient) = parse_pubkey(path) else {
+        // Transaction request links are URL-encoded when they carry a query string.
+        let link = percent_decode(rest).ok_or_else(|| invalid("link is not valid percent-encoding".to_string()))?;
+        if !link.starts_with("https://") {
+            return Err(invalid(format!("'{path}' is neither a valid recipient pubkey nor an https link")));
+        }
+        return Ok(SolanaPayParseResponse {
+            request_type: "transaction".to_string(),
+            link: Some(link),
+            ..Default::default()
+        });
+    };
+    let mut resp = SolanaPayParseResponse {
+        request_type: "transfer".to_string(),
+        recipient: Some(recipient.to_string()),
+        ..Default::default()
+    };
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (name, raw) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(raw).ok_or_else(|| invalid(format!("{name} is not valid percent-encoding")))?;
+        let slot = match name {
+            "amount" => &mut resp.amount,
+            "spl-token" => &mut resp.spl_token,
+            "label" => &mut resp.label,
+            "message" => &mut resp.message,
+            "memo" => &mut resp.memo,
+            "reference" => {
+                let Ok(reference) = parse_pubkey(&value) else {
+                    return Err(invalid(format!("reference '{value}' is not a valid pubkey")));
+                };
+                resp.reference.push(reference.to_string());
+                continue;
+            }
+            // Unknown parameters are ignored, as the spec allows.
+            _ => continue,
+        };
+        if slot.replace(value).is_some() {
+            return Err(invalid(format!("{name} appears more than once")));
+        }
+    }
+    if let Some(mint) = resp.spl_token.take() {
+        let Ok(parsed) = parse_pubkey(&mint) else {
+            return Err(invalid(format!("spl-token '{mint}' is not a valid pubkey")));
+        };
+        resp.spl_token = Some(parsed.to_string());
+    }
+    if let Some(amount) = resp.amount.as_deref() {
+        validate_pay_amount(amount, resp.spl_token.is_none())?;
+    }
+    Ok(resp)
+}
+
+fn format_sol(lamports: u64) -> String {
+    let whole = lamports / LAMPORTS_PER_SOL;
+    let frac = lamports % LAMPORTS_PER_SOL;
+    if frac == 0 {
+        return whole.to_string();
+    }
+    format!("{whole}.{frac:09}").trim_end_matches('0').to_string()
+}
+
+#[derive(Clone, Copy)]
+enum DataEncoding {
+    Base64,
+    Base58,
+    Hex,
+}
+
+fn parse_encoding(value: Option<&str>) -> Result<DataEncoding, ApiError> {
+    match value {
+        None | Some("base64") => Ok(DataEncoding::Base64),
+        Some("base58") => Ok(DataEncoding::Base58),
+        Some(other) => Err(ApiError::InvalidInput(format!("Unknown encoding '{other}', expected base64 or base58"))),
+    }
+}
+
+/// Encoding for secrets and signatures, which unlike instruction data may also be hex.
+fn parse_output_encoding(value: Option<&str>, default: DataEncoding) -> Result<DataEncoding, ApiError> {
+    match value {
+        None => Ok(default),
+        Some("base58") => Ok(DataEncoding::Base58),
+        Some("base64") => Ok(DataEncoding::Base64),
+        Some("hex") => Ok(DataEncoding::Hex),
+        Some(other) => Err(ApiError::InvalidInput(format!("Unknown encoding '{other}', expected base58, base64 or hex"))),
+    }
+}
+
+fn encode_data(bytes: &[u8], encoding: DataEncoding) -> String {
+    match encoding {
+        DataEncoding::Base64 => general_purpose::STANDARD.encode(bytes),
+        DataEncoding::Base58 => bytes.to_base58(),
+        DataEncoding::Hex => hex::encode(bytes),
+    }
+}
+
+fn instruction_response(ix: &Instruction, encoding: DataEncoding) -> InstructionResponse {
+    InstructionResponse {
+        program_id: ix.program_id.to_string(),
+        accounts: ix.accounts.iter().map(|meta| AccountMetaCamel {
+            pubkey: meta.pubkey.to_string(),
+            is_signer: meta.is_signer,
+            is_writable: Some(meta.is_writable),
+        }).collect(),
+        instruction_data: encode_data(&ix.data, encoding),
+    }
+}
+
+fn parse_instruction(input: &InstructionInput) -> Result<Instruction, ApiError> {
+    let program_id = parse_pubkey(&input.program_id).map_err(|_| ApiError::InvalidPubkey)?;
+    let accounts = input.accounts.iter().map(|meta| {
+        let pubkey = parse_pubkey(&meta.pubkey).map_err(|_| ApiError::InvalidPubkey)?;
+        Ok(if meta.is_writable {
+            AccountMeta::new(pubkey, meta.is_signer)
+        } else {
+            AccountMeta::new_readonly(pubkey, meta.is_signer)
+        })
+    }).collect::<Result<Vec<_>, ApiError>>()?;
+    let data = general_purpose::STANDARD
+        .decode(&input.instruction_data)
+        .map_err(|_| ApiError::InvalidInput("Invalid instruction data".to_string()))?;
+    Ok(Instruction { program_id, accounts, data })
+}
+
+/// Signers across `instructions`, deduplicated in first-seen order.
+fn instruction_signers(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut signers: Vec<Pubkey> = Vec::new();
+    for meta in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if meta.is_signer && !signers.contains(&meta.pubkey) {
+            signers.push(meta.pubkey);
+        }
+    }
+    signers
+}
+
+fn compile_message(
+    instructions: &[Instruction],
+    fee_payer: &Pubkey,
+    blockhash: Hash,
+    version: Option<&str>,
+    lookup_tables: &[LookupTableInput],
+) -> Result<VersionedMessage, ApiError> {
+    match version {
+        None | Some("legacy") => {
+            if !lookup_tables.is_empty() {
+                return Err(ApiError::InvalidInput("lookupTables require version \"0\"".to_string()));
+            }
+            Ok(VersionedMessage::Legacy(Message::new_with_blockhash(instructions, Some(fee_payer), &blockhash)))
+        }
+        Some("0") => {
+            let tables = lookup_tables.iter().map(|table| {
+                let key = parse_pubkey(&table.address).map_err(|_| ApiError::InvalidPubkey)?;
+                let addresses = table.addresses.iter()
+                    .map(|address| parse_pubkey(address))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| ApiError::InvalidPubkey)?;
+                Ok(AddressLookupTableAccount { key, addresses })
+            }).collect::<Result<Vec<_>, ApiError>>()?;
+            v0::Message::try_compile(fee_payer, instructions, &tables, blockhash)
+                .map(VersionedMessage::V0)
+                .map_err(|e| ApiError::InvalidInput(format!("Failed to compile message: {e}")))
+        }
+        Some(other) => Err(ApiError::InvalidInput(format!("Unsupported transaction version '{other}', expected \"legacy\" or \"0\""))),
+    }
+}
+
+/// Wire size of a transaction carrying `message` once every required signature is present.
+fn signed_tx_size(message: VersionedMessage) -> Result<usize, ApiError> {
+    let num_signatures = message.header().num_required_signatures as usize;
+    let tx = VersionedTransaction { signatures: vec![Signature::default(); num_signatures], message };
+    bincode::serialized_size(&tx)
+        .map(|size| size as usize)
+        .map_err(|_| ApiError::Serialization)
+}
+
+fn version_and_lookups(message: &VersionedMessage) -> (&'static str, Vec<LookupResponse>) {
+    match message {
+        VersionedMessage::Legacy(_) => ("legacy", Vec::new()),
+        VersionedMessage::V0(message) => ("0", message.address_table_lookups.iter().map(|lookup| LookupResponse {
+            address: lookup.account_key.to_string(),
+            writable_indexes: lookup.writable_indexes.clone(),
+            readonly_indexes: lookup.readonly_indexes.clone(),
+        }).collect()),
+    }
+}
+
+fn coption_pubkey(value: COption<Pubkey>) -> Option<String> {
+    match value {
+        COption::Some(pubkey) => Some(pubkey.to_string()),
+        COption::None => None,
+    }
+}
+
+/// Unpacks SPL token instruction data into its variant name and fields.
+fn decode_token_instruction(data: &[u8]) -> Result<(&'static str, serde_json::Value), ApiError> {
+    let ix = TokenInstruction::unpack(data)
+        .map_err(|e| ApiError::InvalidInput(format!("Invalid token instruction data: {e}")))?;
+    let decoded = match ix {
+        TokenInstruction::InitializeMint { decimals, mint_authority, freeze_authority } => ("InitializeMint", serde_json::json!({
+            "decimals": decimals,
+            "mintAuthority": mint_authority.to_string(),
+            "freezeAuthority": coption_pubkey(freeze_authority),
+        })),
+        TokenInstruction::InitializeMint2 { decimals, mint_authority, freeze_authority } => ("InitializeMint2", serde_json::json!({
+            "decimals": decimals,
+            "mintAuthority": mint_authority.to_string(),
+            "freezeAuthority": coption_pubkey(freeze_authority),
+        })),
+        TokenInstruction::InitializeAccount => ("InitializeAccount", serde_json::json!({})),
+        TokenInstruction::InitializeAccount2 { owner } => ("InitializeAccount2", serde_json::json!({ "owner": owner.to_string() })),
+        TokenInstruction::InitializeAccount3 { owner } => ("InitializeAccount3", serde_json::json!({ "owner": owner.to_string() })),
+        TokenInstruction::InitializeMultisig { m } => ("InitializeMultisig", serde_json::json!({ "m": m })),
+        TokenInstruction::InitializeMultisig2 { m } => ("InitializeMultisig2", serde_json::json!({ "m": m })),
+        TokenInstruction::Transfer { amount } => ("Transfer", serde_json::json!({ "amount": amount })),
+        TokenInstruction::Approve { amount } => ("Approve", serde_json::json!({ "amount": amount })),
+        TokenInstruction::Revoke => ("Revoke", serde_json::json!({})),
+        TokenInstruction::SetAuthority { authority_type, new_authority } => ("SetAuthority", serde_json::json!({
+            "authorityType": format!("{authority_type:?}"),
+            "newAuthority": coption_pubkey(new_authority),
+        })),
+        TokenInstruction::MintTo { amount } => ("MintTo", serde_json::json!({ "amount": amount })),
+        TokenInstruction::Burn { amount } => ("Burn", serde_json::json!({ "amount": amount })),
+        TokenInstruction::CloseAccount => ("CloseAccount", serde_json::json!({})),
+        TokenInstruction::FreezeAccount => ("FreezeAccount", serde_json::json!({})),
+        TokenInstruction::ThawAccount => ("ThawAccount", serde_json::json!({})),
+        TokenInstruction::TransferChecked { amount, decimals } => ("TransferChecked", serde_json::json!({ "amount": amount, "decimals": decimals })),
+        TokenInstruction::ApproveChecked { amount, decimals } => ("ApproveChecked", serde_json::json!({ "amount": amount, "decimals": decimals })),
+        TokenInstruction::MintToChecked { amount, decimals } => ("MintToChecked", serde_json::json!({ "amount": amount, "decimals": decimals })),
+        TokenInstruction::BurnChecked { amount, decimals } => ("BurnChecked", serde_json::json!({ "amount": amount, "decimals": decimals })),
+        TokenInstruction::SyncNative => ("SyncNative", serde_json::json!({})),
+        TokenInstruction::GetAccountDataSize => ("GetAccountDataSize", serde_json::json!({})),
+        TokenInstruction::InitializeImmutableOwner => ("InitializeImmutableOwner", serde_json::json!({})),
+        TokenInstruction::AmountToUiAmount { amount } => ("AmountToUiAmount", serde_json::json!({ "amount": amount })),
+        TokenInstruction::UiAmountToAmount { ui_amount } => ("UiAmountToAmount", serde_json::json!({ "uiAmount": ui_amount })),
+    };
+    Ok(decoded)
+}
+
+const DEFAULT_RPC_URL: &str = "https://api.devnet.solana.com";
+
+const DEFAULT_BLOCKHASH_CACHE_TTL_MS: u64 = 2_000;
+
+struct CachedBlockhash {
+    blockhash: Hash,
+    last_valid_block_height: u64,
+    fetched: Instant,
+}
+
+/// Latest blockhash at the client's default commitment, shared by builders so bursts of requests
+/// don't each hit RPC. Entries older than `ttl` are refetched on demand, and
+/// `spawn_refresh` keeps the entry warm in the background.
+struct BlockhashCache {
+    ttl: Duration,
+    entry: RwLock<Option<CachedBlockhash>>,
+}
+
+impl BlockhashCache {
+    fn new(ttl: Duration) -> Self {
+        BlockhashCache { ttl, entry: RwLock::new(None) }
+    }
+
+    fn from_env() -> Self {
+        let ttl_ms = env::var("BLOCKHASH_CACHE_TTL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&ms| ms > 0)
+            .unwrap_or(DEFAULT_BLOCKHASH_CACHE_TTL_MS);
+        Self::new(Duration::from_millis(ttl_ms))
+    }
+
+    fn cached(&self) -> Option<(Hash, u64)> {
+        let entry = self.entry.read().unwrap_or_else(|e| e.into_inner());
+        entry
+            .as_ref()
+            .filter(|entry| entry.fetched.elapsed() < self.ttl)
+            .map(|entry| (entry.blockhash, entry.last_valid_block_height))
+    }
+
+    async fn refresh(&self, client: &RpcClient) -> Result<(Hash, u64), ApiError> {
+        let (blockhash, last_valid_block_height) = client
+            .get_latest_blockhash_with_commitment(client.commitment())
+            .await
+            .map_err(|e| ApiError::Rpc(e.to_string()))?;
+        let mut entry = self.entry.write().unwrap_or_else(|e| e.into_inner());
+        *entry = Some(CachedBlockhash { blockhash, last_valid_block_height, fetched: Instant::now() });
+        Ok((blockhash, last_valid_block_height))
+    }
+
+    /// Returns the cached blockhash, fetching a new one if it is stale or `fresh` is set.
+    async fn latest(&self, client: &RpcClient, fresh: bool) -> Result<(Hash, u64), ApiError> {
+        match self.cached() {
+            Some(cached) if !fresh => Ok(cached),
+            _ => self.refresh(client).await,
+        }
+    }
+
+    /// Refreshes the cache every `ttl` for the lifetime of the process.
+    fn spawn_refresh(self: &Arc<Self>, client: Arc<RpcClient>) {
+        let cache = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(cache.ttl);
+            loop {
+                interval.tick().await;
+                if let Err(e) = cache.refresh(&client).await {
+                    tracing::warn!("blockhash refresh failed: {e}");
+                }
+            }
+        });
+    }
+}
+
+/// Parses a per-request commitment, falling back to `default` (normally the
+/// shared client's configured commitment) when none is given.
+fn parse_commitment(value: Option<&str>, default: CommitmentConfig) -> Result<CommitmentConfig, ApiError> {
+    match value {
+        None => Ok(default),
+        Some("confirmed") => Ok(CommitmentConfig::confirmed()),
+        Some("processed") => Ok(CommitmentConfig::processed()),
+        Some("finalized") => Ok(CommitmentConfig::finalized()),
+        Some(other) => Err(ApiError::InvalidInput(format!(
+            "Unknown commitment '{other}', expected one of processed, confirmed, finalized"
+        ))),
+    }
+}
+
+fn decode_transaction(encoded: &str) -> Option<VersionedTransaction> {
+    let bytes = general_purpose::STANDARD.decode(encoded).ok()?;
+    bincode::deserialize(&bytes).ok()
+}
+
+/// Decodes a base64 legacy or v0 message and checks its header against its
+/// account keys, so signer slices taken from the header are in bounds.
+fn decode_message(encoded: &str) -> Option<VersionedMessage> {
+    let bytes = general_purpose::STANDARD.decode(encoded.trim()).ok()?;
+    let message: VersionedMessage = bincode::deserialize(&bytes).ok()?;
+    message.sanitize().ok()?;
+    Some(message)
+}
+
+/// Decodes a 64-byte ed25519 signature given as base64 or, failing that,
+/// base58. Surrounding whitespace is ignored.
+fn decode_signature(encoded: &str) -> Option<Signature> {
+    let encoded = encoded.trim();
+    let bytes = general_purpose::STANDARD
+        .decode(encoded)
+        .ok()
+        .filter(|bytes| bytes.len() == 64)
+        .or_else(|| encoded.from_base58().ok().filter(|bytes| bytes.len() == 64))?;
+    Signature::try_from(bytes.as_slice()).ok()
+}
+
+const SIWS_HEADER_SUFFIX: &str = " wants you to sign in with your Solana account:";
+
+/// Renders the canonical SIWS text: header, address, optional statement, then
+/// the optional `Key: value` fields, each block separated by a blank line.
+fn siws_message_text(fields: &SiwsFields) -> String {
+    let mut message = format!("{}{SIWS_HEADER_SUFFIX}\n{}", fields.domain, fields.address);
+    if let Some(statement) = &fields.statement {
+        message.push_str(&format!("\n\n{statement}"));
+    }
+    let mut lines = Vec::new();
+    let optional = [
+        ("URI", &fields.uri),
+        ("Version", &fields.version),
+        ("Chain ID", &fields.chain_id),
+        ("Nonce", &fields.nonce),
+        ("Issued At", &fields.issued_at),
+        ("Expiration Time", &fields.expiration_time),
+        ("Not Before", &fields.not_before),
+        ("Request ID", &fields.request_id),
+    ];
+    for (label, value) in optional {
+        if let Some(value) = value {
+            lines.push(format!("{label}: {value}"));
+        }
+    }
+    if !fields.resources.is_empty() {
+        lines.push("Resources:".to_string());
+        lines.extend(fields.resources.iter().map(|r| format!("- {r}")));
+    }
+    if !lines.is_empty() {
+        message.push_str(&format!("\n\n{}", lines.join("\n")));
+    }
+    message
+}
+
+/// Parses text produced by `siws_message_text`. Re-rendering the parsed fields
+/// must reproduce the input exactly, so non-canonical messages are rejected.
+fn parse_siws_message(message: &str) -> Result<SiwsFields, ApiError> {
+    let invalid = |reason: &str| ApiError::InvalidInput(format!("Invalid SIWS message: {reason}"));
+    let mut lines = message.split('\n');
+    let domain = lines
+        .next()
+        .and_then(|line| line.strip_suffix(SIWS_HEADER_SUFFIX))
+        .filter(|domain| !domain.is_empty())
+        .ok_or_else(|| invalid("missing header"))?;
+    let address = lines.next().ok_or_else(|| invalid("missing address"))?;
+    let mut fields = SiwsFields { domain: domain.to_string(), address: address.to_string(), ..Default::default() };
+    let rest: Vec<&str> = lines.collect();
+    let mut blocks = rest.split(|line| line.is_empty()).filter(|block| !block.is_empty());
+    let mut block = blocks.next();
+    let is_field = |line: &str| {
+        ["URI: ", "Version: ", "Chain ID: ", "Nonce: ", "Issued At: ", "Expiration Time: ", "Not Before: ", "Request ID: "]
+            .iter()
+            .any(|prefix| line.starts_with(prefix))
+            || line == "Resources:"
+    };
+    if let Some(lines) = block.filter(|lines| !is_field(lines[0])) {
+        fields.statement = Some(lines.join("\n"));
+        block = blocks.next();
+    }
+    let mut in_resources = false;
+    for line in block.unwrap_or_default() {
+        if in_resources {
+            let resource = line.strip_prefix("- ").ok_or_else(|| invalid("malformed resource"))?;
+            fields.resources.push(resource.to_string());
+            continue;
+        }
+        let (label, value) = match line.split_once(": ") {
+            Some(pair) => pair,
+            None if *line == "Resources:" => {
+                in_resources = true;
+                continue;
+            }
+            None => return Err(invalid(&format!("unexpected line '{line}'"))),
+        };
+        let slot = match label {
+            "URI" => &mut fields.uri,
+            "Version" => &mut fields.version,
+            "Chain ID" => &mut fields.chain_id,
+            "Nonce" => &mut fields.nonce,
+            "Issued At" => &mut fields.issued_at,
+            "Expiration Time" => &mut fields.expiration_time,
+            "Not Before" => &mut fields.not_before,
+            "Request ID" => &mut fields.request_id,
+            _ => return Err(invalid(&format!("unknown field '{label}'"))),
+        };
+        *slot = Some(value.to_string());
+    }
+    if blocks.next().is_some() || siws_message_text(&fields) != message {
+        return Err(invalid("not in canonical form"));
+    }
+    Ok(fields)
+}
+
+/// Default Solana BIP44 path, matching `solana-keygen` and most wallets.
+const DEFAULT_DERIVATION_PATH: &str = "m/44'/501'/0'/0'";
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const DEFAULT_VANITY_TIMEOUT_MS: u64 = 10_000;
+const MAX_VANITY_TIMEOUT_MS: u64 = 60_000;
+const MAX_KEYPAIR_BATCH: usize = 1000;
+
+/// Generates keypairs until one's pubkey starts with `prefix`, giving up at `deadline`.
+/// Returns the match (if any) and the number of keypairs tried.
+fn search_vanity_keypair(prefix: &str, case_insensitive: bool, deadline: Instant) -> (Option<Keypair>, u64) {
+    let prefix = if case_insensitive { prefix.to_lowercase() } else { prefix.to_string() };
+    let mut attempts = 0;
+    while Instant::now() < deadline {
+        attempts += 1;
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey().to_string();
+        let matched = if case_insensitive {
+            pubkey.to_lowercase().starts_with(&prefix)
+        } else {
+            pubkey.starts_with(&prefix)
+        };
+        if matched {
+            return (Some(keypair), attempts);
+        }
+    }
+    (None, attempts)
+}
+
+/// Parses a base58 pubkey, ignoring surrounding whitespace such as a trailing
+/// newline copied from a file.
+fn parse_pubkey(value: &str) -> Result<Pubkey, ApiError> {
+    Pubkey::from_str(value.trim()).map_err(|_| ApiError::InvalidPubkey)
+}
+
+/// Builds a keypair from decoded secret bytes in either common export format:
+///
+/// - 32 bytes: the raw ed25519 seed, as exported by many non-Solana tools.
+/// - 64 bytes: the seed followed by its public key, as in Solana CLI keypair files.
+///   The public half must match the one derived from the seed.
+fn keypair_from_secret_bytes(bytes: &[u8]) -> Result<Keypair, ApiError> {
+    match bytes.len() {
+        32 => keypair_from_seed(bytes).map_err(|_| ApiError::InvalidSecret),
+        64 => {
+            let keypair = Keypair::from_bytes(bytes).map_err(|_| ApiError::InvalidSecret)?;
+            // `from_bytes` does not check that the trailing public key belongs to the secret half.
+            let matches = keypair_from_seed(&bytes[..32]).map(|derived| derived.pubkey() == keypair.pubkey());
+            if !matches.unwrap_or(false) {
+                return Err(ApiError::InvalidSecret);
+            }
+            Ok(keypair)
+        }
+        len => Err(ApiError::SecretLength(len)),
+    }
+}
+
+/// Decodes a 32-byte seed in the tagged encoding or, when untagged, whichever
+/// of base58 and base64 yields 32 bytes.
+fn decode_seed(seed: &str, encoding: Option<&str>) -> Result<[u8; 32], ApiError> {
+    let seed = seed.trim();
+    let bytes = match encoding {
+        Some("base58") => seed.from_base58().ok(),
+        Some("base64") => general_purpose::STANDARD.decode(seed).ok(),
+        Some(other) => {
+            return Err(ApiError::InvalidInput(format!("Unknown seedEncoding '{other}', expected base58 or base64")));
+        }
+        None => {
+            let base58 = seed.from_base58().ok();
+            let base64 = general_purpose::STANDARD.decode(seed).ok();
+            match (base58, base64) {
+                (Some(bytes), _) if bytes.len() == 32 => Some(bytes),
+                (_, Some(bytes)) if bytes.len() == 32 => Some(bytes),
+                (base58, base64) => base58.or(base64),
+            }
+        }
+    };
+    let Some(bytes) = bytes else {
+        return Err(ApiError::InvalidInput("seed is not valid base58 or base64".to_string()));
+    };
+    <[u8; 32]>::try_from(bytes.as_slice())
+        .map_err(|_| ApiError::InvalidInput(format!("Seed must be exactly 32 bytes, got {}", bytes.len())))
+}
+
+/// Parses a base58 secret key, trimming surrounding whitespace first.
+fn parse_secret(value: &str) -> Result<Keypair, ApiError> {
+    let bytes = value.trim().from_base58().map_err(|_| ApiError::InvalidSecret)?;
+    keypair_from_secret_bytes(&bytes)
+}
+
+/// Parses a pubkey that must be able to sign, rejecting off-curve addresses such as PDAs.
+fn parse_signer_pubkey(value: &str) -> Result<Pubkey, ApiError> {
+    let pubkey = parse_pubkey(value).map_err(|_| ApiError::InvalidPubkey)?;
+    if !pubkey.is_on_curve() {
+        return Err(ApiError::OffCurve(pubkey));
+    }
+    Ok(pubkey)
+}
+
+/// Largest memo that still fits a single-signer transaction in one packet.
+const MAX_MEMO_LEN: usize = 566;
+
+/// Largest mint decimals accepted; more precision than this is almost always a typo.
+const MAX_DECIMALS: u8 = 9;
+
+/// Runtime cap on compute units a single transaction may request.
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Denominator for token-2022 transfer fee basis points.
+const MAX_FEE_BASIS_POINTS: u16 = 10_000;
+
+/// Transfer fee as charged by the token-2022 program: rounded up, capped at `max_fee`.
+fn calculate_transfer_fee(amount: u64, fee_basis_points: u16, max_fee: u64) -> u64 {
+    if amount == 0 || fee_basis_points == 0 {
+        return 0;
+    }
+    let numerator = amount as u128 * fee_basis_points as u128;
+    let fee = numerator.div_ceil(MAX_FEE_BASIS_POINTS as u128);
+    u64::try_from(fee).unwrap_or(u64::MAX).min(max_fee)
+}
+
+/// Process start time and the latest readiness probe, captured in `main` and
+/// attached as request data. `/health` only reads it; `/health/ready` probes.
+struct HealthState {
+    started: Instant,
+    started_at: SystemTime,
+    last_rpc_check: RwLock<Option<HealthCheck>>,
+}
+
+impl HealthState {
+    fn new() -> Self {
+        HealthState { started: Instant::now(), started_at: SystemTime::now(), last_rpc_check: RwLock::new(None) }
+    }
+}
+
+#[derive(Clone, Serialize, JsonSchema)]
+struct HealthCheck {
+    /// `"ok"`, `"fail"`, or `"unknown"` before the first readiness probe.
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// Unix timestamp, in seconds, of the probe this result came from.
+    #[serde(rename = "checkedAt", skip_serializing_if = "Option::is_none")]
+    checked_at: Option<u64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct HealthChecks {
+    rpc: HealthCheck,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct HealthResponse {
+    /// `"DEGRADED"` when the last probe of any check failed, otherwise `"OK"`.
+    status: &'static str,
+    #[serde(rename = "uptimeSeconds")]
+    uptime_seconds: u64,
+    /// Unix timestamp, in seconds, of process start.
+    #[serde(rename = "startedAt")]
+    started_at: u64,
+    checks: HealthChecks,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct VersionResponse {
+    service: &'static str,
+    version: &'static str,
+    #[serde(rename = "solanaSdk")]
+    solana_sdk: &'static str,
+    #[serde(rename = "splToken")]
+    spl_token: &'static str,
+}
+
+// --- Instruction Builders ---
+
+fn build_create_token(req: &CreateTokenRequest) -> Result<Instruction, ApiError> {
+    let mint_authority = parse_pubkey(&req.mint_authority);
+    let mint = parse_pubkey(&req.mint);
+    if mint_authority.is_err() || mint.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let freeze_authority = match req.freeze_authority.as_deref() {
+        Some(value) => match parse_pubkey(value) {
+            Ok(pubkey) => Some(pubkey),
+            Err(_) => return Err(ApiError::InvalidPubkey),
+        },
+        None => None,
+    };
+    if req.decimals > MAX_DECIMALS {
+        return Err(ApiError::InvalidInput(format!("decimals must be <= {MAX_DECIMALS}")));
+    }
+    token_instruction::initialize_mint(
+        &spl_token::id(),
+        &mint.unwrap(),
+        &mint_authority.unwrap(),
+        freeze_authority.as_ref(),
+        req.decimals,
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+/// Allocates a mint account owned by the token program and initializes it
+/// with `build_create_token`. Unless overridden, the account is funded with
+/// the rent-exempt minimum for `Mint::LEN` (82) bytes under the default rent
+/// parameters.
+fn build_create_mint_full(req: &CreateMintFullRequest) -> Result<Vec<Instruction>, ApiError> {
+    let payer = parse_pubkey(&req.payer)?;
+    let init_ix = build_create_token(&req.mint)?;
+    // Already validated by `build_create_token`.
+    let mint = parse_pubkey(&req.mint.mint)?;
+    let lamports = req.lamports.unwrap_or_else(|| Rent::default().minimum_balance(Mint::LEN));
+    Ok(vec![
+        system_instruction::create_account(&payer, &mint, lamports, Mint::LEN as u64, &spl_token::id()),
+        init_ix,
+    ])
+}
+
+fn build_mint_token(req: &MintTokenRequest) -> Result<Instruction, ApiError> {
+    let mint = parse_pubkey(&req.mint);
+    let destination = parse_pubkey(&req.destination);
+    let authority = parse_pubkey(&req.authority);
+    if mint.is_err() || destination.is_err() || authority.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    // A multisig authority is only an account; its listed signers are the ones that sign.
+    let signers = req.signers.iter().map(|s| parse_signer_pubkey(s)).collect::<Result<Vec<_>, _>>()?;
+    if signers.is_empty() {
+        parse_signer_pubkey(&req.authority)?;
+    }
+    let signer_refs: Vec<&Pubkey> = signers.iter().collect();
+    token_instruction::mint_to(
+        &spl_token::id(),
+        &mint.unwrap(),
+        &destination.unwrap(),
+        &authority.unwrap(),
+        &signer_refs,
+        req.amount,
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+fn build_mint_token_checked(req: &MintTokenCheckedRequest) -> Result<Instruction, ApiError> {
+    let mint = parse_pubkey(&req.mint);
+    let destination = parse_pubkey(&req.destination);
+    if mint.is_err() || destination.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let authority = parse_signer_pubkey(&req.authority)?;
+    token_instruction::mint_to_checked(
+        &spl_token::id(),
+        &mint.unwrap(),
+        &destination.unwrap(),
+        &authority,
+        &[],
+        req.amount,
+        req.decimals,
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+fn build_send_token(req: &SendTokenRequest) -> Result<Instruction, ApiError> {
+    let source = parse_pubkey(&req.source);
+    let destination = parse_pubkey(&req.destination);
+    let mint = parse_pubkey(&req.mint);
+    if source.is_err() || destination.is_err() || mint.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let owner = parse_signer_pubkey(&req.owner)?;
+    if req.amount == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    token_instruction::transfer(
+        &spl_token::id(),
+        &source.unwrap(),
+        &destination.unwrap(),
+        &owner,
+        &[],
+        req.amount,
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+fn build_send_token_checked(req: &SendTokenCheckedRequest) -> Result<Instruction, ApiError> {
+    let source = parse_pubkey(&req.source);
+    let destination = parse_pubkey(&req.destination);
+    let mint = parse_pubkey(&req.mint);
+    if source.is_err() || destination.is_err() || mint.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let owner = parse_signer_pubkey(&req.owner)?;
+    if req.amount == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    token_instruction::transfer_checked(
+        &spl_token::id(),
+        &source.unwrap(),
+        &mint.unwrap(),
+        &destination.unwrap(),
+        &owner,
+        &[],
+        req.amount,
+        req.decimals,
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+/// Emits one `TransferChecked` per entry, all drawing from the owner's associated token account.
+fn build_send_token_multi(req: &SendTokenMultiRequest) -> Result<Vec<Instruction>, ApiError> {
+    let Ok(mint) = parse_pubkey(&req.mint) else {
+        return Err(ApiError::InvalidPubkey);
+    };
+    let owner = parse_signer_pubkey(&req.owner)?;
+    if req.transfers.is_empty() {
+        return Err(ApiError::MissingFields);
+    }
+    let source = get_associated_token_address(&owner, &mint);
+    req.transfers.iter().enumerate().map(|(index, transfer)| {
+        let entry = |e: ApiError| ApiError::Entry("Transfer", index, Box::new(e));
+        let destination = parse_pubkey(&transfer.destination).map_err(|_| entry(ApiError::InvalidPubkey))?;
+        if destination == source {
+            return Err(entry(ApiError::InvalidInput("Source and destination must differ".to_string())));
+        }
+        if transfer.amount == 0 {
+            return Err(entry(ApiError::ZeroAmount));
+        }
+        token_instruction::transfer_checked(
+            &spl_token::id(),
+            &source,
+            &mint,
+            &destination,
+            &owner,
+            &[],
+            transfer.amount,
+            req.decimals,
+        )
+        .map_err(|e| entry(ApiError::Instruction(e.to_string())))
+    }).collect()
+}
+
+fn build_burn_token(req: &BurnTokenRequest) -> Result<Instruction, ApiError> {
+    let account = parse_pubkey(&req.account);
+    let mint = parse_pubkey(&req.mint);
+    if account.is_err() || mint.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let owner = parse_signer_pubkey(&req.owner)?;
+    if req.amount == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    token_instruction::burn(
+        &spl_token::id(),
+        &account.unwrap(),
+        &mint.unwrap(),
+        &owner,
+        &[],
+        req.amount,
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+fn build_burn_token_checked(req: &BurnTokenCheckedRequest) -> Result<Instruction, ApiError> {
+    let account = parse_pubkey(&req.account);
+    let mint = parse_pubkey(&req.mint);
+    if account.is_err() || mint.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let owner = parse_signer_pubkey(&req.owner)?;
+    if req.amount == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    token_instruction::burn_checked(
+        &spl_token::id(),
+        &account.unwrap(),
+        &mint.unwrap(),
+        &owner,
+        &[],
+        req.amount,
+        req.decimals,
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+fn build_close_account(req: &CloseAccountRequest) -> Result<Instruction, ApiError> {
+    let account = parse_pubkey(&req.account);
+    let destination = parse_pubkey(&req.destination);
+    if account.is_err() || destination.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let owner = parse_signer_pubkey(&req.owner)?;
+    token_instruction::close_account(
+        &spl_token::id(),
+        &account.unwrap(),
+        &destination.unwrap(),
+        &owner,
+        &[],
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+fn build_approve(req: &ApproveRequest) -> Result<Instruction, ApiError> {
+    let account = parse_pubkey(&req.account);
+    let delegate = parse_pubkey(&req.delegate);
+    if account.is_err() || delegate.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let owner = parse_signer_pubkey(&req.owner)?;
+    if req.amount == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    token_instruction::approve(
+        &spl_token::id(),
+        &account.unwrap(),
+        &delegate.unwrap(),
+        &owner,
+        &[],
+        req.amount,
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+fn build_revoke(req: &RevokeRequest) -> Result<Instruction, ApiError> {
+    let account = parse_pubkey(&req.account);
+    if account.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let owner = parse_signer_pubkey(&req.owner)?;
+    token_instruction::revoke(
+        &spl_token::id(),
+        &account.unwrap(),
+        &owner,
+        &[],
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+fn build_freeze_account(req: &FreezeAccountRequest) -> Result<Instruction, ApiError> {
+    let account = parse_pubkey(&req.account);
+    let mint = parse_pubkey(&req.mint);
+    if account.is_err() || mint.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let authority = parse_signer_pubkey(&req.authority)?;
+    token_instruction::freeze_account(
+        &spl_token::id(),
+        &account.unwrap(),
+        &mint.unwrap(),
+        &authority,
+        &[],
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+fn build_thaw_account(req: &FreezeAccountRequest) -> Result<Instruction, ApiError> {
+    let account = parse_pubkey(&req.account);
+    let mint = parse_pubkey(&req.mint);
+    if account.is_err() || mint.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let authority = parse_signer_pubkey(&req.authority)?;
+    token_instruction::thaw_account(
+        &spl_token::id(),
+        &account.unwrap(),
+        &mint.unwrap(),
+        &authority,
+        &[],
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+fn build_set_authority(req: &SetAuthorityRequest) -> Result<Instruction, ApiError> {
+    let account = parse_pubkey(&req.account);
+    if account.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let current_authority = parse_signer_pubkey(&req.current_authority)?;
+    let new_authority = match req.new_authority.as_deref() {
+        None | Some("") => None,
+        Some(value) => match parse_pubkey(value) {
+            Ok(pubkey) => Some(pubkey),
+            Err(_) => return Err(ApiError::InvalidPubkey),
+        },
+    };
+    let Some(authority_type) = parse_authority_type(&req.authority_type) else {
+        return Err(ApiError::InvalidInput(format!(
+            "Unknown authority type '{}', expected one of MintTokens, FreezeAccount, AccountOwner, CloseAccount",
+            req.authority_type
+        )));
+    };
+    token_instruction::set_authority(
+        &spl_token::id(),
+        &account.unwrap(),
+        new_authority.as_ref(),
+        authority_type,
+        &current_authority,
+        &[],
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+fn build_initialize_account(req: &InitializeAccountRequest) -> Result<Instruction, ApiError> {
+    let account = parse_pubkey(&req.account);
+    let mint = parse_pubkey(&req.mint);
+    let owner = parse_pubkey(&req.owner);
+    if account.is_err() || mint.is_err() || owner.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    token_instruction::initialize_account(
+        &spl_token::id(),
+        &account.unwrap(),
+        &mint.unwrap(),
+        &owner.unwrap(),
+    )
+    .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+/// Allocates a token account owned by the token program and initializes it.
+/// Unless overridden, the account is funded with the rent-exempt minimum for
+/// `Account::LEN` (165) bytes under the default rent parameters.
+fn build_create_token_account_full(req: &CreateTokenAccountFullRequest) -> Result<Vec<Instruction>, ApiError> {
+    let mint = parse_pubkey(&req.mint);
+    let owner = parse_pubkey(&req.owner);
+    if mint.is_err() || owner.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let payer = parse_signer_pubkey(&req.payer)?;
+    let account = parse_signer_pubkey(&req.account)?;
+    let init_ix = token_instruction::initialize_account(&spl_token::id(), &account, &mint.unwrap(), &owner.unwrap())
+        .map_err(|e| ApiError::Instruction(e.to_string()))?;
+    let lamports = req.lamports.unwrap_or_else(|| Rent::default().minimum_balance(TokenAccount::LEN));
+    if lamports == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    Ok(vec![
+        system_instruction::create_account(&payer, &account, lamports, TokenAccount::LEN as u64, &spl_token::id()),
+        init_ix,
+    ])
+}
+
+fn build_initialize_multisig(req: &InitializeMultisigRequest) -> Result<Instruction, ApiError> {
+    let Ok(multisig) = parse_pubkey(&req.multisig) else {
+        return Err(ApiError::InvalidPubkey);
+    };
+    if req.signers.is_empty() || req.signers.len() > token_instruction::MAX_SIGNERS {
+        return Err(ApiError::InvalidInput(format!(
+            "signers must contain between {} and {} pubkeys, got {}",
+            token_instruction::MIN_SIGNERS,
+            token_instruction::MAX_SIGNERS,
+            req.signers.len()
+        )));
+    }
+    if req.m == 0 || req.m as usize > req.signers.len() {
+        return Err(ApiError::InvalidInput(format!(
+            "m must be between 1 and the number of signers ({}), got {}",
+            req.signers.len(),
+            req.m
+        )));
+    }
+    let signers = req.signers.iter().map(|s| parse_signer_pubkey(s)).collect::<Result<Vec<_>, _>>()?;
+    let signer_refs: Vec<&Pubkey> = signers.iter().collect();
+    token_instruction::initialize_multisig(&spl_token::id(), &multisig, &signer_refs, req.m)
+        .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+fn send_sol_lamports(req: &SendSolRequest) -> Result<u64, ApiError> {
+    match (req.lamports, req.sol.as_deref()) {
+        (Some(_), Some(_)) => Err(ApiError::InvalidInput("Specify either sol or lamports, not both".to_string())),
+        (Some(lamports), None) => Ok(lamports),
+        (None, Some(sol)) => parse_sol_amount(sol),
+        (None, None) => Err(ApiError::MissingFields),
+    }
+}
+
+fn build_send_sol(req: &SendSolRequest) -> Result<Instruction, ApiError> {
+    let to = parse_pubkey(&req.to);
+    if to.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let from = parse_signer_pubkey(&req.from)?;
+    let to = to.unwrap();
+    if from == to {
+        return Err(ApiError::InvalidInput("Source and destination must differ".to_string()));
+    }
+    let lamports = send_sol_lamports(req)?;
+    if lamports == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    Ok(system_instruction::transfer(&from, &to, lamports))
+}
+
+fn build_send_sol_with_seed(req: &SendSolWithSeedRequest) -> Result<Instruction, ApiError> {
+    let from = parse_pubkey(&req.from_pubkey);
+    let owner = parse_pubkey(&req.from_owner);
+    let to = parse_pubkey(&req.to);
+    if from.is_err() || owner.is_err() || to.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let base = parse_signer_pubkey(&req.from_base)?;
+    let (from, owner, to) = (from.unwrap(), owner.unwrap(), to.unwrap());
+    if req.seed.len() > MAX_SEED_LEN {
+        return Err(ApiError::InvalidInput(format!("seed must be at most {MAX_SEED_LEN} bytes")));
+    }
+    let derived = Pubkey::create_with_seed(&base, &req.seed, &owner)
+        .map_err(|e| ApiError::InvalidInput(format!("Failed to derive address: {e}")))?;
+    if derived != from {
+        return Err(ApiError::InvalidInput("fromPubkey does not match fromBase, seed and fromOwner".to_string()));
+    }
+    if from == to {
+        return Err(ApiError::InvalidInput("Source and destination must differ".to_string()));
+    }
+    if req.lamports == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    Ok(system_instruction::transfer_with_seed(&from, &base, req.seed.clone(), &owner, &to, req.lamports))
+}
+
+fn build_send_sol_multi(req: &SendSolMultiRequest) -> Result<Vec<Instruction>, ApiError> {
+    let from = parse_signer_pubkey(&req.from)?;
+    if req.recipients.is_empty() {
+        return Err(ApiError::MissingFields);
+    }
+    req.recipients.iter().enumerate().map(|(index, recipient)| {
+        let entry = |e: ApiError| ApiError::Entry("Recipient", index, Box::new(e));
+        let to = parse_pubkey(&recipient.to).map_err(|_| entry(ApiError::InvalidPubkey))?;
+        if to == from {
+            return Err(entry(ApiError::InvalidInput("Source and destination must differ".to_string())));
+        }
+        if recipient.lamports == 0 {
+            return Err(entry(ApiError::ZeroAmount));
+        }
+        Ok(system_instruction::transfer(&from, &to, recipient.lamports))
+    }).collect()
+}
+
+/// Responds with the instructions and, when they overflow one packet, a warning to split them.
+fn multi_transfer_response(instructions: &[Instruction], fee_payer: &Pubkey, encoding: DataEncoding) -> (StatusCode, Json<ApiResponse>) {
+    let message = VersionedMessage::Legacy(Message::new_with_blockhash(instructions, Some(fee_payer), &Hash::default()));
+    let estimated_tx_size = match signed_tx_size(message) {
+        Ok(size) => size,
+        Err(e) => return error(e),
+    };
+    let warning = (estimated_tx_size > PACKET_DATA_SIZE).then(|| format!(
+        "{} instructions need about {estimated_tx_size} bytes, over the {PACKET_DATA_SIZE}-byte transaction limit; split them across transactions",
+        instructions.len()
+    ));
+    let resp = MultiTransferResponse {
+        instructions: instructions.iter().map(|ix| instruction_response(ix, encoding)).collect(),
+        estimated_tx_size,
+        warning,
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+fn build_create_account(req: &CreateAccountRequest) -> Result<Instruction, ApiError> {
+    let payer = parse_pubkey(&req.payer);
+    let new_account = parse_pubkey(&req.new_account);
+    let owner = parse_pubkey(&req.owner);
+    if payer.is_err() || new_account.is_err() || owner.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    if req.lamports == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    if req.space > MAX_PERMITTED_DATA_LENGTH {
+        return Err(ApiError::InvalidInput(format!("space must be <= {MAX_PERMITTED_DATA_LENGTH}")));
+    }
+    Ok(system_instruction::create_account(
+        &payer.unwrap(),
+        &new_account.unwrap(),
+        req.lamports,
+        req.space,
+        &owner.unwrap(),
+    ))
+}
+
+fn build_assign(req: &AssignRequest) -> Result<Instruction, ApiError> {
+    let Ok(owner) = parse_pubkey(&req.owner) else {
+        return Err(ApiError::InvalidPubkey);
+    };
+    let account = parse_signer_pubkey(&req.account)?;
+    Ok(system_instruction::assign(&account, &owner))
+}
+
+fn build_allocate(req: &AllocateRequest) -> Result<Instruction, ApiError> {
+    let account = parse_signer_pubkey(&req.account)?;
+    if req.space == 0 || req.space > MAX_PERMITTED_DATA_LENGTH {
+        return Err(ApiError::InvalidInput(format!("space must be between 1 and {MAX_PERMITTED_DATA_LENGTH}")));
+    }
+    Ok(system_instruction::allocate(&account, req.space))
+}
+
+fn build_create_nonce(req: &CreateNonceRequest) -> Result<Vec<Instruction>, ApiError> {
+    let Ok(authority) = parse_pubkey(&req.authority) else {
+        return Err(ApiError::InvalidPubkey);
+    };
+    let payer = parse_signer_pubkey(&req.payer)?;
+    let nonce_account = parse_signer_pubkey(&req.nonce_account)?;
+    let lamports = req.lamports.unwrap_or_else(|| Rent::default().minimum_balance(NonceState::size()));
+    if lamports == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    Ok(system_instruction::create_nonce_account(&payer, &nonce_account, &authority, lamports))
+}
+
+fn build_advance_nonce(req: &AdvanceNonceRequest) -> Result<Vec<Instruction>, ApiError> {
+    let Ok(nonce_account) = parse_pubkey(&req.nonce_account) else {
+        return Err(ApiError::InvalidPubkey);
+    };
+    let authority = parse_signer_pubkey(&req.authority)?;
+    Ok(vec![system_instruction::advance_nonce_account(&nonce_account, &authority)])
+}
+
+fn build_withdraw_nonce(req: &WithdrawNonceRequest) -> Result<Vec<Instruction>, ApiError> {
+    let nonce_account = parse_pubkey(&req.nonce_account);
+    let to = parse_pubkey(&req.to);
+    if nonce_account.is_err() || to.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let authority = parse_signer_pubkey(&req.authority)?;
+    if req.lamports == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    Ok(vec![system_instruction::withdraw_nonce_account(
+        &nonce_account.unwrap(),
+        &authority,
+        &to.unwrap(),
+        req.lamports,
+    )])
+}
+
+/// Allocates the stake account and initializes it with the given authorities and lockup.
+fn build_create_stake(req: &CreateStakeRequest) -> Result<Vec<Instruction>, ApiError> {
+    let staker = parse_pubkey(&req.staker);
+    let withdrawer = parse_pubkey(&req.withdrawer);
+    if staker.is_err() || withdrawer.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let custodian = match req.lockup.custodian.as_deref() {
+        Some(value) => match parse_pubkey(value) {
+            Ok(pubkey) => pubkey,
+            Err(_) => return Err(ApiError::InvalidPubkey),
+        },
+        None => Pubkey::default(),
+    };
+    let payer = parse_signer_pubkey(&req.payer)?;
+    let stake_account = parse_signer_pubkey(&req.stake_account)?;
+    if req.lamports == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    let authorized = Authorized { staker: staker.unwrap(), withdrawer: withdrawer.unwrap() };
+    let lockup = Lockup { unix_timestamp: req.lockup.unix_timestamp, epoch: req.lockup.epoch, custodian };
+    Ok(stake_instruction::create_account(&payer, &stake_account, &authorized, &lockup, req.lamports))
+}
+
+fn build_delegate_stake(req: &DelegateStakeRequest) -> Result<Vec<Instruction>, ApiError> {
+    let stake_account = parse_pubkey(&req.stake_account);
+    let vote_account = parse_pubkey(&req.vote_account);
+    if stake_account.is_err() || vote_account.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let authority = parse_signer_pubkey(&req.authority)?;
+    Ok(vec![stake_instruction::delegate_stake(&stake_account.unwrap(), &authority, &vote_account.unwrap())])
+}
+
+fn build_deactivate_stake(req: &DeactivateStakeRequest) -> Result<Vec<Instruction>, ApiError> {
+    let Ok(stake_account) = parse_pubkey(&req.stake_account) else {
+        return Err(ApiError::InvalidPubkey);
+    };
+    let authority = parse_signer_pubkey(&req.authority)?;
+    Ok(vec![stake_instruction::deactivate_stake(&stake_account, &authority)])
+}
+
+fn build_withdraw_stake(req: &WithdrawStakeRequest) -> Result<Vec<Instruction>, ApiError> {
+    let stake_account = parse_pubkey(&req.stake_account);
+    let destination = parse_pubkey(&req.destination);
+    if stake_account.is_err() || destination.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    let authority = parse_signer_pubkey(&req.authority)?;
+    if req.lamports == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    Ok(vec![stake_instruction::withdraw(
+        &stake_account.unwrap(),
+        &authority,
+        &destination.unwrap(),
+        req.lamports,
+        None,
+    )])
+}
+
+fn build_create_lookup_table(req: &CreateLookupTableRequest) -> Result<(Instruction, Pubkey), ApiError> {
+    let Ok(authority) = parse_pubkey(&req.authority) else {
+        return Err(ApiError::InvalidPubkey);
+    };
+    let payer = parse_signer_pubkey(&req.payer)?;
+    Ok(lut_instruction::create_lookup_table(authority, payer, req.recent_slot))
+}
+
+fn build_extend_lookup_table(req: &ExtendLookupTableRequest) -> Result<Vec<Instruction>, ApiError> {
+    let Ok(table) = parse_pubkey(&req.table) else {
+        return Err(ApiError::InvalidPubkey);
+    };
+    let authority = parse_signer_pubkey(&req.authority)?;
+    let payer = req.payer.as_deref().map(parse_signer_pubkey).transpose()?;
+    if req.addresses.is_empty() {
+        return Err(ApiError::MissingFields);
+    }
+    if req.addresses.len() > LOOKUP_TABLE_MAX_ADDRESSES {
+        return Err(ApiError::InvalidInput(format!(
+            "A lookup table holds at most {LOOKUP_TABLE_MAX_ADDRESSES} addresses, got {}",
+            req.addresses.len()
+        )));
+    }
+    let Ok(addresses) = req.addresses.iter().map(|a| parse_pubkey(a)).collect::<Result<Vec<_>, _>>() else {
+        return Err(ApiError::InvalidPubkey);
+    };
+    Ok(vec![lut_instruction::extend_lookup_table(table, authority, payer, addresses)])
+}
+
+fn build_create_ata(req: &CreateAtaRequest) -> Result<Instruction, ApiError> {
+    let funder = parse_pubkey(&req.funder);
+    let owner = parse_pubkey(&req.owner);
+    let mint = parse_pubkey(&req.mint);
+    if funder.is_err() || owner.is_err() || mint.is_err() {
+        return Err(ApiError::InvalidPubkey);
+    }
+    Ok(create_associated_token_account(
+        &funder.unwrap(),
+        &owner.unwrap(),
+        &mint.unwrap(),
+        &spl_token::id(),
+    ))
+}
+
+/// Creates the owner's wrapped SOL account if needed, funds it and syncs its token balance.
+fn build_wrap_sol(req: &WrapSolRequest) -> Result<Vec<Instruction>, ApiError> {
+    let owner = parse_signer_pubkey(&req.owner)?;
+    if req.lamports == 0 {
+        return Err(ApiError::ZeroAmount);
+    }
+    let mint = spl_token::native_mint::id();
+    let ata = get_associated_token_address(&owner, &mint);
+    let sync_ix = token_instruction::sync_native(&spl_token::id(), &ata)
+        .map_err(|e| ApiError::Instruction(e.to_string()))?;
+    Ok(vec![
+        create_associated_token_account_idempotent(&owner, &owner, &mint, &spl_token::id()),
+        system_instruction::transfer(&owner, &ata, req.lamports),
+        sync_ix,
+    ])
+}
+
+/// Closes the owner's wrapped SOL account, returning its lamports to the owner.
+fn build_unwrap_sol(req: &UnwrapSolRequest) -> Result<Vec<Instruction>, ApiError> {
+    let owner = parse_signer_pubkey(&req.owner)?;
+    let ata = get_associated_token_address(&owner, &spl_token::native_mint::id());
+    token_instruction::close_account(&spl_token::id(), &ata, &owner, &owner, &[])
+        .map(|ix| vec![ix])
+        .map_err(|e| ApiError::Instruction(e.to_string()))
+}
+
+fn build_memo(req: &MemoRequest) -> Result<Instruction, ApiError> {
+    if req.memo.is_empty() {
+        return Err(ApiError::MissingFields);
+    }
+    if req.memo.len() > MAX_MEMO_LEN {
+        return Err(ApiError::InvalidInput(format!("Memo must be at most {MAX_MEMO_LEN} bytes, got {}", req.memo.len())));
+    }
+    let signers = req.signers.iter().map(|s| parse_signer_pubkey(s)).collect::<Result<Vec<_>, _>>()?;
+    let signer_refs: Vec<&Pubkey> = signers.iter().collect();
+    Ok(spl_memo::build_memo(req.memo.as_bytes(), &signer_refs))
+}
+
+fn build_compute_unit_limit(req: &ComputeUnitLimitRequest) -> Result<Instruction, ApiError> {
+    if req.units == 0 || req.units > MAX_COMPUTE_UNIT_LIMIT {
+        return Err(ApiError::InvalidInput(format!("units must be between 1 and {MAX_COMPUTE_UNIT_LIMIT}")));
+    }
+    Ok(ComputeBudgetInstruction::set_compute_unit_limit(req.units))
+}
+
+fn build_compute_unit_price(req: &ComputeUnitPriceRequest) -> Result<Instruction, ApiError> {
+    Ok(ComputeBudgetInstruction::set_compute_unit_price(req.micro_lamports))
+}
+
+/// Builds the instructions for one `/batch` operation. Bundled operations such
+/// as `createMintFull` contribute more than one.
+fn build_batch_operation(op: &BatchOperation) -> Result<Vec<Instruction>, ApiError> {
+    let ix = match op {
+        BatchOperation::CreateMintFull(req) => return build_create_mint_full(req),
+        BatchOperation::CreateTokenAccountFull(req) => return build_create_token_account_full(req),
+        BatchOperation::CreateToken(req) => build_create_token(req),
+        BatchOperation::MintToken(req) => build_mint_token(req),
+        BatchOperation::MintTokenChecked(req) => build_mint_token_checked(req),
+        BatchOperation::SendSol(req) => build_send_sol(req),
+        BatchOperation::SendToken(req) => build_send_token(req),
+        BatchOperation::SendTokenChecked(req) => build_send_token_checked(req),
+        BatchOperation::BurnToken(req) => build_burn_token(req),
+        BatchOperation::BurnTokenChecked(req) => build_burn_token_checked(req),
+        BatchOperation::CloseAccount(req) => build_close_account(req),
+        BatchOperation::Approve(req) => build_approve(req),
+        BatchOperation::Revoke(req) => build_revoke(req),
+        BatchOperation::FreezeAccount(req) => build_freeze_account(req),
+        BatchOperation::ThawAccount(req) => build_thaw_account(req),
+        BatchOperation::SetAuthority(req) => build_set_authority(req),
+        BatchOperation::InitializeMultisig(req) => build_initialize_multisig(req),
+        BatchOperation::CreateAccount(req) => build_create_account(req),
+        BatchOperation::CreateAta(req) => build_create_ata(req),
+        BatchOperation::Memo(req) => build_memo(req),
+        BatchOperation::ComputeUnitLimit(req) => build_compute_unit_limit(req),
+        BatchOperation::ComputeUnitPrice(req) => build_compute_unit_price(req),
+    }?;
+    Ok(vec![ix])
+}
+
+// --- Endpoints ---
+
+#[handler]
+async fn generate_keypair(Query(query): Query<GenerateKeypairQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_output_encoding(query.encoding.as_deref(), DataEncoding::Base58) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    // CSPRNG work stays off the async workers.
+    let keypair = match tokio::task::spawn_blocking(Keypair::new).await {
+        Ok(keypair) => keypair,
+        Err(_) => return error(ApiError::Internal("Keypair generation failed".to_string())),
+    };
+    let resp = KeypairResponse {
+        pubkey: keypair.pubkey().to_string(),
+        secret: encode_data(&keypair.to_bytes(), encoding),
+        bytes: query.as_bytes.then(|| keypair.to_bytes().to_vec()),
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn generate_keypair_batch(Json(req): Json<KeypairBatchRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.count == 0 || req.count > MAX_KEYPAIR_BATCH {
+        return error(ApiError::InvalidInput(format!(
+            "count must be between 1 and {MAX_KEYPAIR_BATCH}, got {}",
+            req.count
+        )));
+    }
+    let encoding = match parse_output_encoding(req.encoding.as_deref(), DataEncoding::Base58) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let generate = tokio::task::spawn_blocking(move || {
+        (0..req.count).map(|_| {
+            let keypair = Keypair::new();
+            KeypairResponse {
+                pubkey: keypair.pubkey().to_string(),
+                secret: encode_data(&keypair.to_bytes(), encoding),
+                bytes: None,
+            }
+        }).collect()
+    });
+    let keypairs = match generate.await {
+        Ok(keypairs) => keypairs,
+        Err(_) => return error(ApiError::Internal("Keypair generation failed".to_string())),
+    };
+    match serde_json::to_value(KeypairBatchResponse { keypairs }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn import_keypair(Json(req): Json<ImportKeypairRequest>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_output_encoding(req.encoding.as_deref(), DataEncoding::Base58) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let bytes = match (req.secret, req.bytes) {
+        (Some(_), Some(_)) => return error(ApiError::InvalidInput("Specify either secret or bytes, not both".to_string())),
+        (Some(secret), None) => match secret.trim().from_base58() {
+            Ok(bytes) => bytes,
+            Err(_) => return error(ApiError::InvalidSecret),
+        },
+        (None, Some(bytes)) => bytes,
+        (None, None) => return error(ApiError::MissingFields),
+    };
+    let keypair = match keypair_from_secret_bytes(&bytes) {
+        Ok(keypair) => keypair,
+        Err(e) => return error(e),
+    };
+    // Seeds are expanded, so the response is always the 64-byte format.
+    let resp = KeypairResponse {
+        pubkey: keypair.pubkey().to_string(),
+        secret: encode_data(&keypair.to_bytes(), encoding),
+        bytes: Some(keypair.to_bytes().to_vec()),
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn keypair_from_seed_bytes(Json(req): Json<SeedKeypairRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.seed.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let encoding = match parse_output_encoding(req.encoding.as_deref(), DataEncoding::Base58) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let seed = match decode_seed(&req.seed, req.seed_encoding.as_deref()) {
+        Ok(seed) => seed,
+        Err(e) => return error(e),
+    };
+    let Ok(keypair) = keypair_from_seed(&seed) else {
+        return error(ApiError::InvalidSecret);
+    };
+    let resp = KeypairResponse {
+        pubkey: keypair.pubkey().to_string(),
+        secret: encode_data(&keypair.to_bytes(), encoding),
+        bytes: None,
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn keypair_from_mnemonic(Json(req): Json<MnemonicKeypairRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.mnemonic.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let encoding = match parse_output_encoding(req.encoding.as_deref(), DataEncoding::Base58) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let mnemonic = match bip39::Mnemonic::parse_normalized(req.mnemonic.trim()) {
+        Ok(mnemonic) => mnemonic,
+        Err(e) => return error(ApiError::InvalidInput(format!("Invalid mnemonic: {e}"))),
+    };
+    // PBKDF2 seed stretching takes milliseconds of CPU; keep it off the async workers.
+    let passphrase = req.passphrase.unwrap_or_default();
+    let seed = match tokio::task::spawn_blocking(move || mnemonic.to_seed(passphrase)).await {
+        Ok(seed) => seed,
+        Err(_) => return error(ApiError::Internal("Keypair derivation failed".to_string())),
+    };
+    let path_str = req.derivation_path.unwrap_or_else(|| DEFAULT_DERIVATION_PATH.to_string());
+    let path = match DerivationPath::from_absolute_path_str(&path_str) {
+        Ok(path) => path,
+        Err(e) => return error(ApiError::InvalidInput(format!("Invalid derivation path: {e}"))),
+    };
+    let keypair = match keypair_from_seed_and_derivation_path(&seed, Some(path)) {
+        Ok(keypair) => keypair,
+        Err(e) => return error(ApiError::InvalidInput(format!("Failed to derive keypair: {e}"))),
+    };
+    let resp = MnemonicKeypairResponse {
+        pubkey: keypair.pubkey().to_string(),
+        secret: encode_data(&keypair.to_bytes(), encoding),
+        derivation_path: path_str,
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn vanity_keypair(Json(req): Json<VanityKeypairRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.prefix.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let encoding = match parse_output_encoding(req.encoding.as_deref(), DataEncoding::Base58) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let valid_char = |c: char| {
+        BASE58_ALPHABET.contains(c)
+            || (req.case_insensitive
+                && (BASE58_ALPHABET.contains(c.to_ascii_lowercase()) || BASE58_ALPHABET.contains(c.to_ascii_uppercase())))
+    };
+    if !req.prefix.chars().all(valid_char) {
+        return error(ApiError::InvalidInput("prefix contains characters that are not valid base58".to_string()));
+    }
+    let timeout_ms = req.timeout_ms.unwrap_or(DEFAULT_VANITY_TIMEOUT_MS).min(MAX_VANITY_TIMEOUT_MS);
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    let prefix = req.prefix.clone();
+    let search = tokio::task::spawn_blocking(move || {
+        search_vanity_keypair(&prefix, req.case_insensitive, deadline)
+    });
+    let (keypair, attempts) = match search.await {
+        Ok(result) => result,
+        Err(_) => return error(ApiError::Internal("Vanity search failed".to_string())),
+    };
+    let Some(keypair) = keypair else {
+        return error(ApiError::Timeout(format!(
+            "No match for prefix '{}' after {attempts} attempts in {timeout_ms}ms",
+            req.prefix
+        )));
+    };
+    let resp = VanityKeypairResponse {
+        pubkey: keypair.pubkey().to_string(),
+        secret: encode_data(&keypair.to_bytes(), encoding),
+        attempts,
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn create_token(Json(req): Json<CreateTokenRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_create_token(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn create_mint_full(Json(req): Json<CreateMintFullRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_create_mint_full(&req) {
+        Ok(instructions) => instructions.iter().map(|ix| instruction_response(ix, encoding)).collect(),
+        Err(e) => return error(e),
+    };
+    match serde_json::to_value(InstructionsResponse { instructions }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn mint_token(Json(req): Json<MintTokenRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_mint_token(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn mint_token_checked(Json(req): Json<MintTokenCheckedRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_mint_token_checked(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn sign_message(Json(req): Json<SignMessageRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.message.is_empty() || req.secret.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let encoding = match parse_output_encoding(req.encoding.as_deref(), DataEncoding::Base64) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let keypair = match parse_secret(&req.secret) {
+        Ok(keypair) => keypair,
+        Err(e) => return error(e),
+    };
+    let signature = keypair.sign_message(req.message.as_bytes());
+    let resp = SignMessageResponse {
+        signature: encode_data(signature.as_ref(), encoding),
+        signature_base58: signature.as_ref().to_base58(),
+        signature_base64: general_purpose::STANDARD.encode(signature.as_ref()),
+        public_key: keypair.pubkey().to_string(),
+        message: req.message,
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+const MAX_SIGN_BATCH: usize = 1000;
+
+/// Signs every message with one key, decoded once for the whole batch.
+#[handler]
+async fn sign_message_batch(Json(req): Json<SignMessageBatchRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.secret.is_empty() || req.messages.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    if req.messages.len() > MAX_SIGN_BATCH {
+        return error(ApiError::InvalidInput(format!(
+            "messages must contain at most {MAX_SIGN_BATCH} entries, got {}",
+            req.messages.len()
+        )));
+    }
+    if let Some(index) = req.messages.iter().position(|message| message.is_empty()) {
+        return error(ApiError::Entry("Message", index, Box::new(ApiError::MissingFields)));
+    }
+    let encoding = match parse_output_encoding(req.encoding.as_deref(), DataEncoding::Base64) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let keypair = match parse_secret(&req.secret) {
+        Ok(keypair) => keypair,
+        Err(e) => return error(e),
+    };
+    let signatures = req
+        .messages
+        .iter()
+        .map(|message| encode_data(keypair.sign_message(message.as_bytes()).as_ref(), encoding))
+        .collect();
+    let resp = SignMessageBatchResponse { public_key: keypair.pubkey().to_string(), signatures };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+fn verify_item(item: &VerifyMessageRequest) -> VerifyResult {
+    let invalid = |e: String| VerifyResult { valid: false, error: Some(e) };
+    if item.message.is_empty() || item.signature.is_empty() || item.pubkey.is_empty() {
+        return invalid(ApiError::MissingFields.to_string());
+    }
+    let Ok(pubkey) = parse_pubkey(&item.pubkey) else {
+        return invalid(ApiError::InvalidPubkey.to_string());
+    };
+    let Some(signature) = decode_signature(&item.signature) else {
+        return invalid("signature must be 64 bytes, base64 or base58".to_string());
+    };
+    VerifyResult { valid: signature.verify(&pubkey.to_bytes(), item.message.as_bytes()), error: None }
+}
+
+/// Verifies each item independently; malformed items report `valid: false`
+/// with an `error` instead of failing the batch.
+#[handler]
+async fn verify_message_batch(Json(req): Json<VerifyMessageBatchRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.items.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    if req.items.len() > MAX_SIGN_BATCH {
+        return error(ApiError::InvalidInput(format!(
+            "items must contain at most {MAX_SIGN_BATCH} entries, got {}",
+            req.items.len()
+        )));
+    }
+    let results = req.items.iter().map(verify_item).collect();
+    match serde_json::to_value(VerifyMessageBatchResponse { results }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn verify_message(Json(req): Json<VerifyMessageRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.message.is_empty() || req.signature.is_empty() || req.pubkey.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let pubkey = parse_pubkey(&req.pubkey);
+    if let (Ok(pubkey), Some(signature)) = (pubkey, decode_signature(&req.signature)) {
+        let valid = signature.verify(&pubkey.to_bytes(), req.message.as_bytes());
+        let resp = VerifyMessageResponse {
+            valid,
+            message: req.message,
+            pubkey: pubkey.to_string(),
+        };
+        return match serde_json::to_value(resp) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        };
+    }
+    error(ApiError::InvalidSignature)
+}
+
+#[handler]
+async fn verify_message_multi(Json(req): Json<VerifyMessageMultiRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.message.is_empty() || req.signature.is_empty() || req.pubkeys.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let Ok(pubkeys) = req.pubkeys.iter().map(|p| parse_pubkey(p)).collect::<Result<Vec<_>, _>>() else {
+        return error(ApiError::InvalidPubkey);
+    };
+    let Some(signature) = decode_signature(&req.signature) else {
+        return error(ApiError::InvalidSignature);
+    };
+    let matched = pubkeys
+        .iter()
+        .find(|pubkey| signature.verify(&pubkey.to_bytes(), req.message.as_bytes()));
+    let resp = VerifyMessageMultiResponse {
+        valid: matched.is_some(),
+        matched_pubkey: matched.map(|pubkey| pubkey.to_string()),
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn build_siws_message(Json(req): Json<SiwsFields>) -> (StatusCode, Json<ApiResponse>) {
+    if req.domain.is_empty() || req.address.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    if parse_pubkey(&req.address).is_err() {
+        return error(ApiError::InvalidPubkey);
+    }
+    let message = siws_message_text(&req);
+    // Newlines or field-like statements would make the message ambiguous to parse back.
+    if parse_siws_message(&message).is_err() {
+        return error(ApiError::InvalidInput(
+            "SIWS fields must be single-line and the statement must not look like a field".to_string(),
+        ));
+    }
+    match serde_json::to_value(SiwsBuildResponse { message }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+/// Checks the signature against the address embedded in the message. Nonce and
+/// time fields are returned for the caller to check against its own session.
+#[handler]
+async fn verify_siws_message(Json(req): Json<SiwsVerifyRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.message.is_empty() || req.signature.is_empty() || req.address.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let fields = match parse_siws_message(&req.message) {
+        Ok(fields) => fields,
+        Err(e) => return error(e),
+    };
+    let (Ok(address), Ok(embedded)) = (parse_pubkey(&req.address), parse_pubkey(&fields.address)) else {
+        return error(ApiError::InvalidPubkey);
+    };
+    let Some(signature) = decode_signature(&req.signature) else {
+        return error(ApiError::InvalidSignature);
+    };
+    let valid = address == embedded && signature.verify(&address.to_bytes(), req.message.as_bytes());
+    match serde_json::to_value(SiwsVerifyResponse { valid, fields }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn send_sol(Json(req): Json<SendSolRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let ix = match build_send_sol(&req) {
+        Ok(ix) => ix,
+        Err(e) => return error(e),
+    };
+    // Already validated by `build_send_sol`.
+    let lamports = send_sol_lamports(&req).unwrap_or_default();
+    let accounts = ix.accounts.iter().map(|meta| meta.pubkey.to_string()).collect();
+    let resp = InstructionResponseSendSol {
+        program_id: ix.program_id.to_string(),
+        accounts,
+        instruction_data: encode_data(&ix.data, encoding),
+        lamports,
+        sol: format_sol(lamports),
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn send_sol_with_seed(Json(req): Json<SendSolWithSeedRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let ix = match build_send_sol_with_seed(&req) {
+        Ok(ix) => ix,
+        Err(e) => return error(e),
+    };
+    let resp = InstructionResponseSendSolWithSeed {
+        program_id: ix.program_id.to_string(),
+        accounts: ix.accounts.iter().map(|meta| meta.pubkey.to_string()).collect(),
+        is_signer: ix.accounts.iter().map(|meta| meta.is_signer).collect(),
+        instruction_data: encode_data(&ix.data, encoding),
+        lamports: req.lamports,
+        sol: format_sol(req.lamports),
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn send_sol_multi(Json(req): Json<SendSolMultiRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_send_sol_multi(&req) {
+        Ok(instructions) => instructions,
+        Err(e) => return error(e),
+    };
+    // Already validated by `build_send_sol_multi`.
+    let from = parse_pubkey(&req.from).unwrap_or_default();
+    multi_transfer_response(&instructions, &from, encoding)
+}
+
+#[handler]
+async fn send_token(Json(req): Json<SendTokenRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_send_token(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn send_token_checked(Json(req): Json<SendTokenCheckedRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_send_token_checked(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn send_token_multi(Json(req): Json<SendTokenMultiRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_send_token_multi(&req) {
+        Ok(instructions) => instructions,
+        Err(e) => return error(e),
+    };
+    // Already validated by `build_send_token_multi`.
+    let owner = parse_pubkey(&req.owner).unwrap_or_default();
+    multi_transfer_response(&instructions, &owner, encoding)
+}
+
+#[handler]
+async fn burn_token(Json(req): Json<BurnTokenRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_burn_token(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn burn_token_checked(Json(req): Json<BurnTokenCheckedRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_burn_token_checked(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn close_account(Json(req): Json<CloseAccountRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_close_account(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn approve(Json(req): Json<ApproveRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_approve(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn revoke(Json(req): Json<RevokeRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_revoke(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn freeze_account(Json(req): Json<FreezeAccountRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_freeze_account(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn thaw_account(Json(req): Json<FreezeAccountRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_thaw_account(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn set_authority(Json(req): Json<SetAuthorityRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_set_authority(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn initialize_account(Json(req): Json<InitializeAccountRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_initialize_account(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn create_token_account_full(Json(req): Json<CreateTokenAccountFullRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_create_token_account_full(&req) {
+        Ok(instructions) => instructions.iter().map(|ix| instruction_response(ix, encoding)).collect(),
+        Err(e) => return error(e),
+    };
+    match serde_json::to_value(InstructionsResponse { instructions }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn initialize_multisig(Json(req): Json<InitializeMultisigRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_initialize_multisig(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn create_account(Json(req): Json<CreateAccountRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_create_account(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn assign_account(Json(req): Json<AssignRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_assign(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn allocate_account(Json(req): Json<AllocateRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_allocate(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn create_nonce(Json(req): Json<CreateNonceRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_create_nonce(&req) {
+        Ok(instructions) => instructions.iter().map(|ix| instruction_response(ix, encoding)).collect(),
+        Err(e) => return error(e),
+    };
+    match serde_json::to_value(InstructionsResponse { instructions }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn advance_nonce(Json(req): Json<AdvanceNonceRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_advance_nonce(&req) {
+        Ok(instructions) => instructions.iter().map(|ix| instruction_response(ix, encoding)).collect(),
+        Err(e) => return error(e),
+    };
+    match serde_json::to_value(InstructionsResponse { instructions }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn withdraw_nonce(Json(req): Json<WithdrawNonceRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_withdraw_nonce(&req) {
+        Ok(instructions) => instructions.iter().map(|ix| instruction_response(ix, encoding)).collect(),
+        Err(e) => return error(e),
+    };
+    match serde_json::to_value(InstructionsResponse { instructions }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn create_stake(Json(req): Json<CreateStakeRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_create_stake(&req) {
+        Ok(instructions) => instructions.iter().map(|ix| instruction_response(ix, encoding)).collect(),
+        Err(e) => return error(e),
+    };
+    match serde_json::to_value(InstructionsResponse { instructions }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn delegate_stake(Json(req): Json<DelegateStakeRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_delegate_stake(&req) {
+        Ok(instructions) => instructions.iter().map(|ix| instruction_response(ix, encoding)).collect(),
+        Err(e) => return error(e),
+    };
+    match serde_json::to_value(InstructionsResponse { instructions }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn deactivate_stake(Json(req): Json<DeactivateStakeRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_deactivate_stake(&req) {
+        Ok(instructions) => instructions.iter().map(|ix| instruction_response(ix, encoding)).collect(),
+        Err(e) => return error(e),
+    };
+    match serde_json::to_value(InstructionsResponse { instructions }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn withdraw_stake(Json(req): Json<WithdrawStakeRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_withdraw_stake(&req) {
+        Ok(instructions) => instructions.iter().map(|ix| instruction_response(ix, encoding)).collect(),
+        Err(e) => return error(e),
+    };
+    match serde_json::to_value(InstructionsResponse { instructions }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn create_lookup_table(Json(req): Json<CreateLookupTableRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let (ix, table) = match build_create_lookup_table(&req) {
+        Ok(result) => result,
+        Err(e) => return error(e),
+    };
+    let resp = CreateLookupTableResponse {
+        instructions: vec![instruction_response(&ix, encoding)],
+        table_address: table.to_string(),
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn extend_lookup_table(Json(req): Json<ExtendLookupTableRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_extend_lookup_table(&req) {
+        Ok(instructions) => instructions.iter().map(|ix| instruction_response(ix, encoding)).collect(),
+        Err(e) => return error(e),
+    };
+    match serde_json::to_value(InstructionsResponse { instructions }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+/// Builds a Solana Pay transfer request URL, with parameters in the order the spec lists them.
+#[handler]
+async fn solana_pay_transfer_url(Json(req): Json<SolanaPayTransferRequest>) -> (StatusCode, Json<ApiResponse>) {
+    let Ok(recipient) = parse_pubkey(&req.recipient) else {
+        return error(ApiError::InvalidPubkey);
+    };
+    let spl_token = match req.spl_token.as_deref().map(parse_pubkey).transpose() {
+        Ok(spl_token) => spl_token,
+        Err(_) => return error(ApiError::InvalidPubkey),
+    };
+    let references = req.reference.map(OneOrMany::into_vec).unwrap_or_default();
+    let references: Result<Vec<Pubkey>, ApiError> = references.iter().map(|reference| parse_pubkey(reference)).collect();
+    let Ok(references) = references else {
+        return error(ApiError::InvalidPubkey);
+    };
+    let mut params = Vec::new();
+    if let Some(amount) = req.amount.as_deref() {
+        if let Err(e) = validate_pay_amount(amount, spl_token.is_none()) {
+            return error(e);
+        }
+        params.push(format!("amount={amount}"));
+    }
+    if let Some(mint) = spl_token {
+        params.push(format!("spl-token={mint}"));
+    }
+    params.extend(references.iter().map(|reference| format!("reference={reference}")));
+    let text_fields = [("label", &req.label), ("message", &req.message), ("memo", &req.memo)];
+    for (name, value) in text_fields {
+        if let Some(value) = value {
+            params.push(format!("{name}={}", percent_encode(value)));
+        }
+    }
+    let mut url = format!("solana:{recipient}");
+    if !params.is_empty() {
+        url.push('?');
+        url.push_str(&params.join("&"));
+    }
+    match serde_json::to_value(SolanaPayUrlResponse { url }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn solana_pay_parse(Json(req): Json<SolanaPayParseRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.url.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    match parse_solana_pay_url(req.url.trim()) {
+        Ok(resp) => match serde_json::to_value(resp) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn derive_ata(Json(req): Json<DeriveAtaRequest>) -> (StatusCode, Json<ApiResponse>) {
+    let owner = parse_pubkey(&req.owner);
+    let mint = parse_pubkey(&req.mint);
+    if owner.is_err() || mint.is_err() {
+        return error(ApiError::InvalidPubkey);
+    }
+    let (owner, mint) = (owner.unwrap(), mint.unwrap());
+    let ata = get_associated_token_address(&owner, &mint);
+    let resp = DeriveAtaResponse {
+        ata: ata.to_string(),
+        owner: owner.to_string(),
+        mint: mint.to_string(),
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn transfer_fee(Json(req): Json<TransferFeeRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.fee_basis_points > MAX_FEE_BASIS_POINTS {
+        return error(ApiError::InvalidInput(format!(
+            "feeBasisPoints must be at most {MAX_FEE_BASIS_POINTS}"
+        )));
+    }
+    let fee = calculate_transfer_fee(req.amount, req.fee_basis_points, req.max_fee);
+    let resp = TransferFeeResponse { fee, net: req.amount - fee };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+/// Parses a UI amount the way the token program's `UiAmountToAmount` does:
+/// digits past `decimals` are rejected rather than rounded, apart from
+/// trailing zeros.
+fn ui_amount_to_amount(ui_amount: &str, decimals: u8) -> Result<u64, ApiError> {
+    let (whole, frac) = ui_amount.split_once('.').unwrap_or((ui_amount, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && frac.is_empty()) || !is_digits(whole) || !is_digits(frac) {
+        return Err(ApiError::InvalidAmount(format!("uiAmount '{ui_amount}' is not a decimal number")));
+    }
+    if frac.trim_end_matches('0').len() > decimals as usize {
+        return Err(ApiError::InvalidAmount(format!("uiAmount has more than {decimals} decimal places")));
+    }
+    spl_token::try_ui_amount_into_amount(ui_amount.to_string(), decimals)
+        .map_err(|_| ApiError::InvalidAmount(format!("uiAmount {ui_amount} with {decimals} decimals exceeds u64")))
+}
+
+#[handler]
+async fn convert_amount(Json(req): Json<ConvertAmountRequest>) -> (StatusCode, Json<ApiResponse>) {
+    let amount = match (req.amount, req.ui_amount.as_deref().map(str::trim)) {
+        (Some(_), Some(_)) => return error(ApiError::InvalidInput("Specify either amount or uiAmount, not both".to_string())),
+        (Some(amount), None) => amount,
+        (None, Some(ui_amount)) => match ui_amount_to_amount(ui_amount, req.decimals) {
+            Ok(amount) => amount,
+            Err(e) => return error(e),
+        },
+        (None, None) => return error(ApiError::MissingFields),
+    };
+    let resp = ConvertAmountResponse {
+        amount: amount.to_string(),
+        ui_amount: spl_token::amount_to_ui_amount_string_trimmed(amount, req.decimals),
+        decimals: req.decimals,
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn create_ata(Json(req): Json<CreateAtaRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let ix = match build_create_ata(&req) {
+        Ok(ix) => ix,
+        Err(e) => return error(e),
+    };
+    let resp = InstructionResponseCreateAta {
+        instruction: instruction_response(&ix, encoding),
+        // The associated token program lists the new account right after the funder.
+        ata: ix.accounts[1].pubkey.to_string(),
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn wrap_sol(Json(req): Json<WrapSolRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_wrap_sol(&req) {
+        Ok(instructions) => instructions.iter().map(|ix| instruction_response(ix, encoding)).collect(),
+        Err(e) => return error(e),
+    };
+    match serde_json::to_value(InstructionsResponse { instructions }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn unwrap_sol(Json(req): Json<UnwrapSolRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    let instructions = match build_unwrap_sol(&req) {
+        Ok(instructions) => instructions.iter().map(|ix| instruction_response(ix, encoding)).collect(),
+        Err(e) => return error(e),
+    };
+    match serde_json::to_value(InstructionsResponse { instructions }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn build_tx(
+    Json(req): Json<BuildTxRequest>,
+    Query(query): Query<FreshQuery>,
+    Data(client): Data<&Arc<RpcClient>>,
+    Data(cache): Data<&Arc<BlockhashCache>>,
+) -> (StatusCode, Json<ApiResponse>) {
+    if req.instructions.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let fee_payer = parse_pubkey(&req.fee_payer);
+    if fee_payer.is_err() {
+        return error(ApiError::InvalidPubkey);
+    }
+    let blockhash = match req.recent_blockhash.as_deref() {
+        Some(value) => match Hash::from_str(value.trim()) {
+            Ok(blockhash) => blockhash,
+            Err(_) => return error(ApiError::InvalidInput("Invalid recent blockhash".to_string())),
+        },
+        None => match cache.latest(client, query.fresh).await {
+            Ok((blockhash, _)) => blockhash,
+            Err(e) => return error(e),
+        },
+    };
+    let instructions: Result<Vec<Instruction>, ApiError> = req.instructions.iter().map(parse_instruction).collect();
+    let instructions = match instructions {
+        Ok(instructions) => instructions,
+        Err(e) => return error(e),
+    };
+    let message = compile_message(
+        &instructions,
+        &fee_payer.unwrap(),
+        blockhash,
+        req.version.as_deref(),
+        &req.lookup_tables,
+    );
+    let message = match message {
+        Ok(message) => message,
+        Err(e) => return error(e),
+    };
+    let num_signers = message.header().num_required_signatures as usize;
+    let signers = message.static_account_keys()[..num_signers]
+        .iter()
+        .map(|key| key.to_string())
+        .collect();
+    let (version, lookups) = version_and_lookups(&message);
+    let resp = BuildTxResponse {
+        message: general_purpose::STANDARD.encode(message.serialize()),
+        recent_blockhash: blockhash.to_string(),
+        signers,
+        version: version.to_string(),
+        lookups,
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+/// Compiles the message offline and returns the bytes an external signer
+/// signs over; no secret or RPC call is involved.
+#[handler]
+async fn message_bytes(Json(req): Json<MessageBytesRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.instructions.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let Ok(fee_payer) = parse_pubkey(&req.fee_payer) else {
+        return error(ApiError::InvalidPubkey);
+    };
+    let Ok(blockhash) = Hash::from_str(req.recent_blockhash.trim()) else {
+        return error(ApiError::InvalidInput("Invalid recent blockhash".to_string()));
+    };
+    let instructions: Result<Vec<Instruction>, ApiError> = req.instructions.iter().map(parse_instruction).collect();
+    let instructions = match instructions {
+        Ok(instructions) => instructions,
+        Err(e) => return error(e),
+    };
+    let message = match compile_message(&instructions, &fee_payer, blockhash, req.version.as_deref(), &req.lookup_tables) {
+        Ok(message) => message,
+        Err(e) => return error(e),
+    };
+    let bytes = message.serialize();
+    let num_signers = message.header().num_required_signatures as usize;
+    let (version, _) = version_and_lookups(&message);
+    let resp = MessageBytesResponse {
+        message_bytes: general_purpose::STANDARD.encode(&bytes),
+        sha256: hex::encode(hash::hash(&bytes).to_bytes()),
+        signers: message.static_account_keys()[..num_signers].iter().map(|key| key.to_string()).collect(),
+        version: version.to_string(),
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn tx_size(Json(req): Json<TxSizeRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.instructions.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let instructions: Result<Vec<Instruction>, ApiError> = req.instructions.iter().map(parse_instruction).collect();
+    let instructions = match instructions {
+        Ok(instructions) => instructions,
+        Err(e) => return error(e),
+    };
+    let fee_payer = match req.fee_payer.as_deref() {
+        Some(value) => match parse_pubkey(value) {
+            Ok(pubkey) => pubkey,
+            Err(_) => return error(ApiError::InvalidPubkey),
+        },
+        None => match instruction_signers(&instructions).first() {
+            Some(pubkey) => *pubkey,
+            None => return error(ApiError::InvalidInput("feePayer is required when no instruction has a signer".to_string())),
+        },
+    };
+    // The blockhash is fixed-width, so a placeholder doesn't change the size.
+    let message = compile_message(
+        &instructions,
+        &fee_payer,
+        Hash::default(),
+        req.version.as_deref(),
+        &req.lookup_tables,
+    );
+    let message = match message {
+        Ok(message) => message,
+        Err(e) => return error(e),
+    };
+    let num_signatures = message.header().num_required_signatures as usize;
+    let message_size = message.serialize().len();
+    let size = match signed_tx_size(message) {
+        Ok(size) => size,
+        Err(e) => return error(e),
+    };
+    let resp = TxSizeResponse {
+        size,
+        message_size,
+        num_signatures,
+        limit: PACKET_DATA_SIZE,
+        fits_in_single_packet: size <= PACKET_DATA_SIZE,
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn tx_signers(Json(req): Json<TxSignersRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.instructions.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let instructions: Result<Vec<Instruction>, ApiError> = req.instructions.iter().map(parse_instruction).collect();
+    let instructions = match instructions {
+        Ok(instructions) => instructions,
+        Err(e) => return error(e),
+    };
+    let signers = instruction_signers(&instructions);
+    let resp = TxSignersResponse {
+        fee_payer: signers.first().map(|pubkey| pubkey.to_string()),
+        signers: signers.iter().map(|pubkey| pubkey.to_string()).collect(),
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn sign_tx(Json(req): Json<SignTxRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.message.is_empty() || req.secrets.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    // Legacy and v0 messages share one wire format, distinguished by the version prefix.
+    let Some(message) = decode_message(&req.message) else {
+        return error(ApiError::InvalidInput("Invalid message".to_string()));
+    };
+    let keypairs: Option<Vec<Keypair>> = req.secrets.iter().map(|secret| {
+        let bytes = secret.trim().from_base58().ok()?;
+        if bytes.len() != 64 {
+            return None;
+        }
+        Keypair::from_bytes(&bytes).ok()
+    }).collect();
+    let Some(keypairs) = keypairs else {
+        return error(ApiError::InvalidSecret);
+    };
+    let signed_bytes = message.serialize();
+    let num_signers = message.header().num_required_signatures as usize;
+    let signer_keys = message.static_account_keys()[..num_signers].to_vec();
+    let mut signatures = vec![Signature::default(); num_signers];
+    for keypair in &keypairs {
+        let Some(slot) = signer_keys.iter().position(|key| *key == keypair.pubkey()) else {
+            return error(ApiError::InvalidInput(format!("{} is not a required signer", keypair.pubkey())));
+        };
+        signatures[slot] = keypair.sign_message(&signed_bytes);
+    }
+    let tx = VersionedTransaction { signatures, message };
+    let signatures: Vec<SignerSignature> = signer_keys
+        .iter()
+        .zip(&tx.signatures)
+        .map(|(pubkey, signature)| SignerSignature {
+            pubkey: pubkey.to_string(),
+            signature: (*signature != Signature::default()).then(|| signature.to_string()),
+        })
+        .collect();
+    let Ok(tx_bytes) = bincode::serialize(&tx) else {
+        return error(ApiError::Serialization);
+    };
+    let resp = SignTxResponse {
+        transaction: general_purpose::STANDARD.encode(tx_bytes),
+        complete: signatures.iter().all(|s| s.signature.is_some()),
+        signatures,
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+/// Places externally produced signatures into the slots of their signers and
+/// returns the assembled transaction.
+#[handler]
+async fn attach_signatures(Json(req): Json<AttachSignaturesRequest>) -> (StatusCode, Json<ApiResponse>) {
+    if req.message.is_empty() || req.signatures.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let Some(message) = decode_message(&req.message) else {
+        return error(ApiError::InvalidInput("Invalid message".to_string()));
+    };
+    let signed_bytes = message.serialize();
+    let num_signers = message.header().num_required_signatures as usize;
+    let signer_keys = message.static_account_keys()[..num_signers].to_vec();
+    let mut signatures = vec![Signature::default(); num_signers];
+    for (index, entry) in req.signatures.iter().enumerate() {
+        let entry_error = |e: ApiError| error(ApiError::Entry("Signature", index, Box::new(e)));
+        let Ok(pubkey) = parse_pubkey(&entry.pubkey) else {
+            return entry_error(ApiError::InvalidPubkey);
+        };
+        let Some(slot) = signer_keys.iter().position(|key| *key == pubkey) else {
+            return entry_error(ApiError::InvalidInput(format!("{pubkey} is not a required signer")));
+        };
+        let Some(signature) = decode_signature(&entry.signature) else {
+            return entry_error(ApiError::InvalidInput("signature must be 64 bytes, base64 or base58".to_string()));
+        };
+        if !signature.verify(&pubkey.to_bytes(), &signed_bytes) {
+            return entry_error(ApiError::InvalidSignature);
+        }
+        signatures[slot] = signature;
+    }
+    let tx = VersionedTransaction { signatures, message };
+    let Ok(tx_bytes) = bincode::serialize(&tx) else {
+        return error(ApiError::Serialization);
+    };
+    let signatures: Vec<SignerSignature> = signer_keys
+        .iter()
+        .zip(&tx.signatures)
+        .map(|(pubkey, signature)| SignerSignature {
+            pubkey: pubkey.to_string(),
+            signature: (*signature != Signature::default()).then(|| signature.to_string()),
+        })
+        .collect();
+    let missing_signers: Vec<String> = signatures
+        .iter()
+        .filter(|s| s.signature.is_none())
+        .map(|s| s.pubkey.clone())
+        .collect();
+    let resp = AttachSignaturesResponse {
+        transaction: general_purpose::STANDARD.encode(tx_bytes),
+        complete: missing_signers.is_empty(),
+        signatures,
+        missing_signers,
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn send_tx(Json(req): Json<SendTxRequest>, Data(client): Data<&Arc<RpcClient>>) -> (StatusCode, Json<ApiResponse>) {
+    if req.transaction.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let Some(tx) = decode_transaction(&req.transaction) else {
+        return error(ApiError::InvalidTransaction);
+    };
+    match client.send_transaction(&tx).await {
+        Ok(signature) => {
+            let resp = SendTxResponse { signature: signature.to_string() };
+            match serde_json::to_value(resp) {
+                Ok(val) => success(val),
+                Err(_) => error(ApiError::Serialization),
+            }
+        }
+        Err(e) => error(ApiError::Rpc(e.to_string())),
+    }
+}
+
+#[handler]
+async fn simulate_tx(Json(req): Json<SendTxRequest>, Data(client): Data<&Arc<RpcClient>>) -> (StatusCode, Json<ApiResponse>) {
+    if req.transaction.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let Some(tx) = decode_transaction(&req.transaction) else {
+        return error(ApiError::InvalidTransaction);
+    };
+    let result = match client.simulate_transaction(&tx).await {
+        Ok(response) => response.value,
+        Err(e) => return error(ApiError::Rpc(e.to_string())),
+    };
+    let err = result.err.map(|e| e.to_string());
+    let resp = SimulateTxResponse {
+        logs: result.logs.unwrap_or_default(),
+        units_consumed: result.units_consumed,
+        err: err.clone(),
+    };
+    let val = match serde_json::to_value(resp) {
+        Ok(val) => val,
+        Err(_) => return error(ApiError::Serialization),
+    };
+    match err {
+        // Failed simulations keep the logs in `data` so clients can see where execution stopped.
+        Some(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse {
+                success: false,
+                data: Some(val),
+                error: Some(format!("Simulation failed: {e}")),
+                error_code: Some(ErrorCode::SimulationFailed),
+                request_id: None,
+            })
+        ),
+        None => success(val),
+    }
+}
+
+#[handler]
+async fn decode_tx(Json(req): Json<SendTxRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    if req.transaction.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let Some(tx) = decode_transaction(&req.transaction) else {
+        return error(ApiError::InvalidTransaction);
+    };
+    let keys = tx.message.static_account_keys();
+    let resolve = |index: u8| keys.get(index as usize).map(|key| key.to_string());
+    let instructions = tx.message.instructions().iter().map(|ix| DecodedInstruction {
+        program_id: resolve(ix.program_id_index).unwrap_or_default(),
+        accounts: ix.accounts.iter().map(|&index| resolve(index)).collect(),
+        instruction_data: encode_data(&ix.data, encoding),
+    }).collect();
+    let (version, address_table_lookups) = version_and_lookups(&tx.message);
+    let resp = DecodeTxResponse {
+        version: version.to_string(),
+        fee_payer: keys.first().map(|key| key.to_string()),
+        recent_blockhash: tx.message.recent_blockhash().to_string(),
+        signatures: tx.signatures.iter().map(|signature| signature.to_string()).collect(),
+        instructions,
+        address_table_lookups,
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn decode_token_ix(Json(req): Json<DecodeTokenInstructionRequest>) -> (StatusCode, Json<ApiResponse>) {
+    let Ok(program_id) = parse_pubkey(&req.program_id) else {
+        return error(ApiError::InvalidPubkey);
+    };
+    if program_id != spl_token::id() {
+        return error(ApiError::InvalidInput(format!(
+            "Unsupported program {program_id}, expected {}",
+            spl_token::id()
+        )));
+    }
+    let Ok(accounts) = req.accounts.iter().map(|account| parse_pubkey(account)).collect::<Result<Vec<_>, _>>() else {
+        return error(ApiError::InvalidPubkey);
+    };
+    let Ok(data) = general_purpose::STANDARD.decode(&req.instruction_data) else {
+        return error(ApiError::InvalidInput("Invalid instruction data".to_string()));
+    };
+    let (instruction_type, params) = match decode_token_instruction(&data) {
+        Ok(decoded) => decoded,
+        Err(e) => return error(e),
+    };
+    let resp = DecodeTokenInstructionResponse {
+        instruction_type: instruction_type.to_string(),
+        params,
+        accounts: accounts.iter().map(Pubkey::to_string).collect(),
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn rpc_blockhash(
+    Query(query): Query<BlockhashQuery>,
+    Data(client): Data<&Arc<RpcClient>>,
+    Data(cache): Data<&Arc<BlockhashCache>>,
+) -> (StatusCode, Json<ApiResponse>) {
+    let commitment = match parse_commitment(query.commitment.as_deref(), client.commitment()) {
+        Ok(commitment) => commitment,
+        Err(e) => return error(e),
+    };
+    // Only blockhashes at the default commitment are cached.
+    let latest = if commitment == client.commitment() {
+        cache.latest(client, query.fresh).await
+    } else {
+        client
+            .get_latest_blockhash_with_commitment(commitment)
+            .await
+            .map_err(|e| ApiError::Rpc(e.to_string()))
+    };
+    match latest {
+        Ok((blockhash, last_valid_block_height)) => {
+            let resp = BlockhashResponse {
+                blockhash: blockhash.to_string(),
+                last_valid_block_height,
+            };
+            match serde_json::to_value(resp) {
+                Ok(val) => success(val),
+                Err(_) => error(ApiError::Serialization),
+            }
+        }
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn rpc_fee(Json(req): Json<FeeRequest>, Query(query): Query<CommitmentQuery>, Data(client): Data<&Arc<RpcClient>>) -> (StatusCode, Json<ApiResponse>) {
+    if req.message.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let commitment = match parse_commitment(query.commitment.as_deref(), client.commitment()) {
+        Ok(commitment) => commitment,
+        Err(e) => return error(e),
+    };
+    let message = general_purpose::STANDARD
+        .decode(&req.message)
+        .ok()
+        .and_then(|bytes| bincode::deserialize::<VersionedMessage>(&bytes).ok());
+    let Some(message) = message else {
+        return error(ApiError::InvalidInput("Invalid message".to_string()));
+    };
+    // `get_fee_for_message` always uses the client's commitment, so the
+    // request is sent directly to honour the `commitment` parameter.
+    let params = serde_json::json!([general_purpose::STANDARD.encode(message.serialize()), commitment]);
+    let fee = client.send::<RpcResponse<Option<u64>>>(RpcRequest::GetFeeForMessage, params).await;
+    match fee {
+        Ok(RpcResponse { value: Some(lamports), .. }) => {
+            let resp = FeeResponse { lamports, sol: format_sol(lamports) };
+            match serde_json::to_value(resp) {
+                Ok(val) => success(val),
+                Err(_) => error(ApiError::Serialization),
+            }
+        }
+        // The cluster returns no fee when it no longer recognizes the blockhash.
+        Ok(RpcResponse { value: None, .. }) => error(ApiError::InvalidInput(
+            "The message's recent blockhash has expired; rebuild it with a fresh blockhash".to_string(),
+        )),
+        Err(e) => error(ApiError::Rpc(e.to_string())),
+    }
+}
+
+async fn rent_exemption(client: &RpcClient, space: usize) -> Result<RentExemption, ApiError> {
+    let lamports = client
+        .get_minimum_balance_for_rent_exemption(space)
+        .await
+        .map_err(|e| ApiError::Rpc(e.to_string()))?;
+    Ok(RentExemption { space, lamports, sol: format_sol(lamports) })
+}
+
+/// Rent-exempt minimum for `space` bytes, or for a mint and a token account
+/// when `space` is omitted.
+#[handler]
+async fn rpc_rent(Json(req): Json<RentRequest>, Data(client): Data<&Arc<RpcClient>>) -> (StatusCode, Json<ApiResponse>) {
+    let resp = match req.space {
+        Some(space) if space as u64 > MAX_PERMITTED_DATA_LENGTH => {
+            return error(ApiError::InvalidInput(format!("space must be <= {MAX_PERMITTED_DATA_LENGTH}")));
+        }
+        Some(space) => rent_exemption(client, space).await.map(RentResponse::Sized),
+        None => match (rent_exemption(client, Mint::LEN).await, rent_exemption(client, TokenAccount::LEN).await) {
+            (Ok(mint), Ok(token_account)) => Ok(RentResponse::Common { mint, token_account }),
+            (Err(e), _) | (_, Err(e)) => Err(e),
+        },
+    };
+    match resp.and_then(|resp| serde_json::to_value(resp).map_err(|_| ApiError::Serialization)) {
+        Ok(val) => success(val),
+        Err(e) => error(e),
+    }
+}
+
+const DEFAULT_CONFIRM_TIMEOUT_MS: u64 = 15_000;
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Polls the signature's status until it reaches the requested commitment or
+/// the timeout elapses. A timeout is reported as `confirmed: false`.
+#[handler]
+async fn rpc_confirm(
+    Json(req): Json<ConfirmTxRequest>,
+    Data(client): Data<&Arc<RpcClient>>,
+    Data(timeouts): Data<&RequestTimeouts>,
+) -> (StatusCode, Json<ApiResponse>) {
+    let Ok(signature) = Signature::from_str(req.signature.trim()) else {
+        return error(ApiError::InvalidSignature);
+    };
+    let commitment = match parse_commitment(req.commitment.as_deref(), client.commitment()) {
+        Ok(commitment) => commitment,
+        Err(e) => return error(e),
+    };
+    let timeout = Duration::from_millis(req.timeout_ms.unwrap_or(DEFAULT_CONFIRM_TIMEOUT_MS)).min(timeouts.confirm_max);
+    let deadline = Instant::now() + timeout;
+    let mut resp = ConfirmTxResponse { confirmed: false, status: None, err: None };
+    loop {
+        let status = match client.get_signature_statuses(&[signature]).await {
+            Ok(response) => response.value.into_iter().next().flatten(),
+            Err(e) => return error(ApiError::Rpc(e.to_string())),
+        };
+        if let Some(status) = status {
+            resp.confirmed = status.satisfies_commitment(commitment);
+            resp.status = status.confirmation_status.as_ref().map(|s| format!("{s:?}").to_lowercase());
+            resp.err = status.err.as_ref().map(|e| e.to_string());
+            if resp.confirmed {
+                break;
+            }
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        tokio::time::sleep(CONFIRM_POLL_INTERVAL.min(deadline - now)).await;
+    }
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn rpc_balance(Path(pubkey): Path<String>, Query(query): Query<CommitmentQuery>, Data(client): Data<&Arc<RpcClient>>) -> (StatusCode, Json<ApiResponse>) {
+    let Ok(address) = parse_pubkey(&pubkey) else {
+        return error(ApiError::InvalidPubkey);
+    };
+    let commitment = match parse_commitment(query.commitment.as_deref(), client.commitment()) {
+        Ok(commitment) => commitment,
+        Err(e) => return error(e),
+    };
+    match client.get_balance_with_commitment(&address, commitment).await {
+        Ok(response) => {
+            let resp = BalanceResponse {
+                pubkey: address.to_string(),
+                lamports: response.value,
+                sol: format_sol(response.value),
+            };
+            match serde_json::to_value(resp) {
+                Ok(val) => success(val),
+                Err(_) => error(ApiError::Serialization),
+            }
+        }
+        Err(e) => error(ApiError::Rpc(e.to_string())),
+    }
+}
+
+#[handler]
+async fn rpc_token_balance(Json(req): Json<DeriveAtaRequest>, Data(client): Data<&Arc<RpcClient>>) -> (StatusCode, Json<ApiResponse>) {
+    let owner = parse_pubkey(&req.owner);
+    let mint = parse_pubkey(&req.mint);
+    if owner.is_err() || mint.is_err() {
+        return error(ApiError::InvalidPubkey);
+    }
+    let ata = get_associated_token_address(&owner.unwrap(), &mint.unwrap());
+    let account = match client.get_account_with_commitment(&ata, client.commitment()).await {
+        Ok(response) => response.value,
+        Err(e) => return error(ApiError::Rpc(e.to_string())),
+    };
+    let resp = if account.is_none() {
+        TokenBalanceResponse {
+            ata: ata.to_string(),
+            amount: "0".to_string(),
+            ui_amount: None,
+            decimals: None,
+            exists: false,
+        }
+    } else {
+        match client.get_token_account_balance_with_commitment(&ata, client.commitment()).await {
+            Ok(response) => TokenBalanceResponse {
+                ata: ata.to_string(),
+                amount: response.value.amount,
+                ui_amount: Some(response.value.ui_amount_string),
+                decimals: Some(response.value.decimals),
+                exists: true,
+            },
+            Err(e) => return error(ApiError::Rpc(e.to_string())),
+        }
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn find_pda(Json(req): Json<FindPdaRequest>) -> (StatusCode, Json<ApiResponse>) {
+    let Ok(program_id) = parse_pubkey(&req.program_id) else {
+        return error(ApiError::InvalidPubkey);
+    };
+    // One seed slot is reserved for the bump.
+    if req.seeds.len() >= MAX_SEEDS {
+        return error(ApiError::InvalidInput(format!("At most {} seeds are allowed", MAX_SEEDS - 1)));
+    }
+    let mut seeds = Vec::with_capacity(req.seeds.len());
+    for (index, seed) in req.seeds.iter().enumerate() {
+        let bytes = match seed {
+            SeedInput::Utf8(value) => Ok(value.as_bytes().to_vec()),
+            SeedInput::Base64(value) => general_purpose::STANDARD.decode(value).map_err(|_| ()),
+            SeedInput::Base58(value) => value.from_base58().map_err(|_| ()),
+        };
+        let Ok(bytes) = bytes else {
+            return error(ApiError::InvalidInput(format!("Seed {index} is not valid for its encoding")));
+        };
+        if bytes.len() > MAX_SEED_LEN {
+            return error(ApiError::InvalidInput(format!("Seed {index} exceeds {MAX_SEED_LEN} bytes")));
+        }
+        seeds.push(bytes);
+    }
+    let seed_refs: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    let Some((address, bump)) = Pubkey::try_find_program_address(&seed_refs, &program_id) else {
+        return error(ApiError::InvalidInput("Unable to find a viable program address bump seed".to_string()));
+    };
+    let resp = FindPdaResponse {
+        address: address.to_string(),
+        bump,
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn validate_pubkey(Json(req): Json<ValidatePubkeyRequest>) -> (StatusCode, Json<ApiResponse>) {
+    let pubkey = parse_pubkey(&req.pubkey).ok();
+    let resp = ValidatePubkeyResponse {
+        valid: pubkey.is_some(),
+        on_curve: pubkey.is_some_and(|pubkey| pubkey.is_on_curve()),
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn memo(Json(req): Json<MemoRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_memo(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn compute_unit_limit(Json(req): Json<ComputeUnitLimitRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_compute_unit_limit(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn compute_unit_price(Json(req): Json<ComputeUnitPriceRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    match build_compute_unit_price(&req) {
+        Ok(ix) => match serde_json::to_value(instruction_response(&ix, encoding)) {
+            Ok(val) => success(val),
+            Err(_) => error(ApiError::Serialization),
+        },
+        Err(e) => error(e),
+    }
+}
+
+#[handler]
+async fn batch(Json(req): Json<BatchRequest>, Query(query): Query<EncodingQuery>) -> (StatusCode, Json<ApiResponse>) {
+    let encoding = match parse_encoding(query.encoding.as_deref()) {
+        Ok(encoding) => encoding,
+        Err(e) => return error(e),
+    };
+    if req.operations.is_empty() {
+        return error(ApiError::MissingFields);
+    }
+    let mut instructions = Vec::with_capacity(req.operations.len());
+    for (index, value) in req.operations.into_iter().enumerate() {
+        // Operations are parsed one at a time so a malformed entry can be reported by index.
+        let ixs = serde_json::from_value::<BatchOperation>(value)
+            .map_err(|e| ApiError::InvalidInput(e.to_string()))
+            .and_then(|op| build_batch_operation(&op));
+        match ixs {
+            Ok(ixs) => instructions.extend(ixs.iter().map(|ix| instruction_response(ix, encoding))),
+            Err(e) => return error(ApiError::Operation(index, Box::new(e))),
+        }
+    }
+    match serde_json::to_value(InstructionsResponse { instructions }) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+/// Reports uptime and the most recent dependency checks without running them.
+fn health_report(state: &HealthState) -> HealthResponse {
+    let rpc = state
+        .last_rpc_check
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or(HealthCheck { status: "unknown", error: None, checked_at: None });
+    HealthResponse {
+        status: if rpc.status == "fail" { "DEGRADED" } else { "OK" },
+        uptime_seconds: state.started.elapsed().as_secs(),
+        started_at: unix_secs(state.started_at),
+        checks: HealthChecks { rpc },
+    }
+}
+
+/// Asks the cluster for `getHealth` and records the result for `/health`.
+async fn probe_rpc(state: &HealthState, client: &RpcClient) -> HealthCheck {
+    let error = match tokio::time::timeout(READY_RPC_TIMEOUT, client.get_health()).await {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(format!("RPC unhealthy: {e}")),
+        Err(_) => Some(format!("RPC did not respond within {}ms", READY_RPC_TIMEOUT.as_millis())),
+    };
+    let check = HealthCheck {
+        status: if error.is_none() { "ok" } else { "fail" },
+        error,
+        checked_at: Some(unix_secs(SystemTime::now())),
+    };
+    *state.last_rpc_check.write().unwrap_or_else(|e| e.into_inner()) = Some(check.clone());
+    check
+}
+
+/// Process status: always 200 and never touches RPC. `checks` carries the
+/// result of the last `/health/ready` probe.
+#[handler]
+async fn health(Data(state): Data<&Arc<HealthState>>) -> (StatusCode, Json<ApiResponse>) {
+    match serde_json::to_value(health_report(state)) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+/// Liveness check: 200 whenever the process can serve requests.
+#[handler]
+async fn health_live() -> (StatusCode, Json<ApiResponse>) {
+    success(serde_json::json!({"status": "OK"}))
+}
+
+/// How long the readiness probe waits for the cluster before reporting 503.
+const READY_RPC_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Readiness check: 200 only while the configured cluster answers `getHealth`.
+#[handler]
+async fn health_ready(Data(state): Data<&Arc<HealthState>>, Data(client): Data<&Arc<RpcClient>>) -> (StatusCode, Json<ApiResponse>) {
+    if let Some(e) = probe_rpc(state, client).await.error {
+        return error(ApiError::Unavailable(e));
+    }
+    match serde_json::to_value(health_report(state)) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn service_version() -> (StatusCode, Json<ApiResponse>) {
+    let resp = VersionResponse {
+        service: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        solana_sdk: env!("SOLANA_SDK_VERSION"),
+        spl_token: env!("SPL_TOKEN_VERSION"),
+    };
+    match serde_json::to_value(resp) {
+        Ok(val) => success(val),
+        Err(_) => error(ApiError::Serialization),
+    }
+}
+
+#[handler]
+async fn render_metrics(Data(metrics): Data<&Arc<Metrics>>) -> Response {
+    let mut buf = Vec::new();
+    if TextEncoder::new().encode(&metrics.registry.gather(), &mut buf).is_err() {
+        return error(ApiError::Internal("Failed to encode metrics".to_string())).into_response();
+    }
+    Response::builder()
+        .content_type(TextEncoder::new().format_type())
+        .body(buf)
+}
+
+#[handler]
+async fn openapi_json() -> Json<serde_json::Value> {
+    Json(openapi_spec())
+}
+
+#[handler]
+async fn swagger_ui() -> Html<&'static str> {
+    Html(SWAGGER_UI_HTML)
+}
+
+// --- OpenAPI ---
+
+const SWAGGER_UI_HTML: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+  <title>Solana HTTP API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" });</script>
+</body>
+</html>
+"##;
+
+/// Collects operations and the component schemas they reference. Schemas are
+/// derived from the request/response structs, so serde renames are reflected.
+struct SpecBuilder {
+    gen: SchemaGenerator,
+    paths: serde_json::Map<String, serde_json::Value>,
+}
+
+impl SpecBuilder {
+    fn new() -> Self {
+        SpecBuilder { gen: SchemaSettings::openapi3().into_generator(), paths: serde_json::Map::new() }
+    }
+
+    fn schema<T: JsonSchema>(&mut self) -> serde_json::Value {
+        serde_json::to_value(self.gen.subschema_for::<T>()).unwrap_or_default()
+    }
+
+    /// Expands the fields of a `Query<T>` struct into query parameters.
+    fn query_params<T: JsonSchema>(&mut self) -> Vec<serde_json::Value> {
+        let schema = serde_json::to_value(T::json_schema(&mut self.gen)).unwrap_or_default();
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+        schema["properties"]
+            .as_object()
+            .map(|props| {
+                props
+                    .iter()
+                    .map(|(name, prop)| serde_json::json!({
+                        "name": name,
+                        "in": "query",
+                        "required": required.contains(&name.as_str()),
+                        "schema": prop,
+                    }))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn add(
+        &mut self,
+        method: &str,
+        path: &str,
+        summary: &str,
+        body: Option<serde_json::Value>,
+        parameters: Vec<serde_json::Value>,
+        data: serde_json::Value,
+    ) {
+        let error = serde_json::json!({ "$ref": "#/components/responses/Error" });
+        let mut op = serde_json::json!({
+            "summary": summary,
+            "responses": {
+                "200": {
+                    "description": "Success",
+                    "content": { "application/json": { "schema": {
+                        "type": "object",
+                        "required": ["success", "data"],
+                        "properties": {
+                            "success": { "type": "boolean", "enum": [true] },
+                            "data": data,
+                            "requestId": { "type": "string" },
+                        },
+                    } } },
+                },
+                "400": error, "401": error, "408": error, "413": error, "429": error, "500": error,
+            },
+        });
+        if !parameters.is_empty() {
+            op["parameters"] = serde_json::Value::Array(parameters);
+        }
+        if let Some(body) = body {
+            op["responses"]["415"] = error.clone();
+            op["requestBody"] = serde_json::json!({
+                "required": true,
+                "content": { "application/json": { "schema": body } },
+            });
+        }
+        let entry = self.paths.entry(path.to_string()).or_insert_with(|| serde_json::json!({}));
+        entry[method] = op;
+    }
+
+    fn post<B: JsonSchema, D: JsonSchema>(&mut self, path: &str, summary: &str) {
+        let body = self.schema::<B>();
+        let data = self.schema::<D>();
+        self.add("post", path, summary, Some(body), Vec::new(), data);
+    }
+
+    /// A POST whose instruction data honours the `encoding` query parameter.
+    fn post_encoded<B: JsonSchema, D: JsonSchema>(&mut self, path: &str, summary: &str) {
+        let body = self.schema::<B>();
+        let data = self.schema::<D>();
+        let params = self.query_params::<EncodingQuery>();
+        self.add("post", path, summary, Some(body), params, data);
+    }
+
+    fn get<Q: JsonSchema, D: JsonSchema>(&mut self, path: &str, summary: &str) {
+        let data = self.schema::<D>();
+        let params = self.query_params::<Q>();
+        self.add("get", path, summary, None, params, data);
+    }
+
+    fn finish(mut self) -> serde_json::Value {
+        self.schema::<ApiResponse>();
+        let schemas = serde_json::to_value(self.gen.definitions()).unwrap_or_default();
+        serde_json::json!({
+            "openapi": "3.0.3",
+            "info": {
+                "title": "Solana HTTP API",
+                "version": env!("CARGO_PKG_VERSION"),
+                "description": "Every JSON response is wrapped in an envelope: \
+                    `{ \"success\": true, \"data\": ... }` or `{ \"success\": false, \"error\": \"...\" }`.",
+            },
+            "paths": self.paths,
+            "components": {
+                "schemas": schemas,
+                "responses": {
+                    "Error": {
+                        "description": "Error envelope",
+                        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/ApiResponse" } } },
+                    },
+                },
+                "securitySchemes": {
+                    "ApiKey": { "type": "apiKey", "in": "header", "name": "x-api-key" },
+                },
+            },
+            "security": [{ "ApiKey": [] }],
+        })
+    }
+}
+
+fn openapi_spec() -> serde_json::Value {
+    let mut spec = SpecBuilder::new();
+    let data = spec.schema::<HealthResponse>();
+    spec.add("get", "/health", "Uptime and the last readiness probe result", None, Vec::new(), data);
+    spec.add(
+        "get",
+        "/health/live",
+        "Liveness check",
+        None,
+        Vec::new(),
+        serde_json::json!({ "type": "object", "properties": { "status": { "type": "string" } } }),
+    );
+    let data = spec.schema::<HealthResponse>();
+    spec.add("get", "/health/ready", "Readiness check against the configured RPC cluster", None, Vec::new(), data);
+    spec.paths["/health/ready"]["get"]["responses"]["503"] = serde_json::json!({ "$ref": "#/components/responses/Error" });
+    let data = spec.schema::<VersionResponse>();
+    spec.add("get", "/version", "Service and dependency versions", None, Vec::new(), data);
+    spec.paths.insert("/metrics".to_string(), serde_json::json!({ "get": {
+        "summary": "Prometheus metrics",
+        "responses": { "200": {
+            "description": "Prometheus text exposition format",
+            "content": { "text/plain": { "schema": { "type": "string" } } },
+        } },
+    } }));
+
+    let params = spec.query_params::<GenerateKeypairQuery>();
+    let data = spec.schema::<KeypairResponse>();
+    spec.add("post", "/keypair", "Generate a new keypair", None, params, data);
+    spec.post::<KeypairBatchRequest, KeypairBatchResponse>("/keypair/batch", "Generate several keypairs at once");
+    spec.post::<ImportKeypairRequest, KeypairResponse>("/keypair/import", "Import a keypair from its secret key");
+    spec.post::<SeedKeypairRequest, KeypairResponse>("/keypair/from-seed", "Derive a keypair from a raw 32-byte seed");
+    spec.post::<MnemonicKeypairRequest, MnemonicKeypairResponse>("/keypair/from-mnemonic", "Derive a keypair from a BIP39 mnemonic");
+    spec.post::<VanityKeypairRequest, VanityKeypairResponse>("/keypair/vanity", "Search for a keypair with a given prefix");
+
+    spec.post_encoded::<CreateTokenRequest, InstructionResponse>("/token/create", "Build an InitializeMint instruction");
+    spec.post_encoded::<CreateMintFullRequest, InstructionsResponse>("/token/create-mint-full", "Build create-account and InitializeMint instructions");
+    spec.post_encoded::<MintTokenRequest, InstructionResponse>("/token/mint", "Build a MintTo instruction");
+    spec.post_encoded::<MintTokenCheckedRequest, InstructionResponse>("/token/mint/checked", "Build a MintToChecked instruction");
+    spec.post_encoded::<BurnTokenRequest, InstructionResponse>("/token/burn", "Build a Burn instruction");
+    spec.post_encoded::<BurnTokenCheckedRequest, InstructionResponse>("/token/burn/checked", "Build a BurnChecked instruction");
+    spec.post_encoded::<CloseAccountRequest, InstructionResponse>("/token/close", "Build a CloseAccount instruction");
+    spec.post_encoded::<ApproveRequest, InstructionResponse>("/token/approve", "Build an Approve instruction");
+    spec.post_encoded::<RevokeRequest, InstructionResponse>("/token/revoke", "Build a Revoke instruction");
+    spec.post_encoded::<FreezeAccountRequest, InstructionResponse>("/token/freeze", "Build a FreezeAccount instruction");
+    spec.post_encoded::<FreezeAccountRequest, InstructionResponse>("/token/thaw", "Build a ThawAccount instruction");
+    spec.post_encoded::<SetAuthorityRequest, InstructionResponse>("/token/set-authority", "Build a SetAuthority instruction");
+    spec.post_encoded::<InitializeAccountRequest, InstructionResponse>("/token/account/init", "Build an InitializeAccount instruction");
+    spec.post_encoded::<CreateTokenAccountFullRequest, InstructionsResponse>("/token/account/create-full", "Build create-account and InitializeAccount instructions");
+    spec.post_encoded::<InitializeMultisigRequest, InstructionResponse>("/token/multisig/create", "Build an InitializeMultisig instruction");
+    spec.post::<DecodeTokenInstructionRequest, DecodeTokenInstructionResponse>("/token/decode", "Decode SPL token instruction data");
+    spec.post::<DeriveAtaRequest, DeriveAtaResponse>("/token/ata/derive", "Derive an associated token account address");
+    spec.post::<ConvertAmountRequest, ConvertAmountResponse>("/token/amount/convert", "Convert between raw and UI token amounts");
+    spec.post::<TransferFeeRequest, TransferFeeResponse>("/token/transfer-fee/calc", "Calculate the token-2022 transfer fee for an amount");
+    spec.post_encoded::<CreateAtaRequest, InstructionResponseCreateAta>("/token/ata/create", "Build a create associated token account instruction");
+    spec.post_encoded::<WrapSolRequest, InstructionsResponse>("/token/wrap", "Build instructions to wrap SOL into the owner's native mint account");
+    spec.post_encoded::<UnwrapSolRequest, InstructionsResponse>("/token/unwrap", "Build an instruction closing the owner's wrapped SOL account");
+
+    spec.post::<SolanaPayTransferRequest, SolanaPayUrlResponse>("/solana-pay/transfer-url", "Build a Solana Pay transfer request URL");
+    spec.post::<SolanaPayParseRequest, SolanaPayParseResponse>("/solana-pay/parse", "Parse a Solana Pay URL into its fields");
+
+    spec.post::<SignMessageRequest, SignMessageResponse>("/message/sign", "Sign a message");
+    spec.post::<SignMessageBatchRequest, SignMessageBatchResponse>("/message/sign/batch", "Sign several messages with one key");
+    spec.post::<VerifyMessageRequest, VerifyMessageResponse>("/message/verify", "Verify a message signature");
+    spec.post::<VerifyMessageBatchRequest, VerifyMessageBatchResponse>("/message/verify/batch", "Verify several message signatures independently");
+    spec.post::<VerifyMessageMultiRequest, VerifyMessageMultiResponse>("/message/verify/multi", "Find which of several pubkeys produced a signature");
+    spec.post::<SiwsFields, SiwsBuildResponse>("/message/siws/build", "Build a Sign-In With Solana message");
+    spec.post::<SiwsVerifyRequest, SiwsVerifyResponse>("/message/siws/verify", "Parse and verify a signed Sign-In With Solana message");
+
+    spec.post_encoded::<SendSolRequest, InstructionResponseSendSol>("/send/sol", "Build a SOL transfer instruction");
+    spec.post_encoded::<SendSolWithSeedRequest, InstructionResponseSendSolWithSeed>("/send/sol/with-seed", "Build a SOL transfer from an address derived with a seed");
+    spec.post_encoded::<SendSolMultiRequest, MultiTransferResponse>("/send/sol/multi", "Build one SOL transfer instruction per recipient");
+    spec.post_encoded::<SendTokenRequest, InstructionResponse>("/send/token", "Build a token Transfer instruction");
+    spec.post_encoded::<SendTokenCheckedRequest, InstructionResponse>("/send/token/checked", "Build a token TransferChecked instruction");
+    spec.post_encoded::<SendTokenMultiRequest, MultiTransferResponse>("/send/token/multi", "Build one TransferChecked instruction per destination");
+    spec.post_encoded::<CreateAccountRequest, InstructionResponse>("/system/create-account", "Build a system CreateAccount instruction");
+    spec.post_encoded::<AssignRequest, InstructionResponse>("/system/assign", "Build a system Assign instruction");
+    spec.post_encoded::<AllocateRequest, InstructionResponse>("/system/allocate", "Build a system Allocate instruction");
+    spec.post_encoded::<CreateNonceRequest, InstructionsResponse>("/nonce/create", "Build instructions to create and initialize a durable nonce account");
+    spec.post_encoded::<AdvanceNonceRequest, InstructionsResponse>("/nonce/advance", "Build an AdvanceNonceAccount instruction");
+    spec.post_encoded::<WithdrawNonceRequest, InstructionsResponse>("/nonce/withdraw", "Build a WithdrawNonceAccount instruction");
+    spec.post_encoded::<CreateStakeRequest, InstructionsResponse>("/stake/create", "Build instructions to create and initialize a stake account");
+    spec.post_encoded::<DelegateStakeRequest, InstructionsResponse>("/stake/delegate", "Build a DelegateStake instruction");
+    spec.post_encoded::<DeactivateStakeRequest, InstructionsResponse>("/stake/deactivate", "Build a Deactivate stake instruction");
+    spec.post_encoded::<WithdrawStakeRequest, InstructionsResponse>("/stake/withdraw", "Build a stake Withdraw instruction");
+    spec.post_encoded::<CreateLookupTableRequest, CreateLookupTableResponse>("/lut/create", "Build a CreateLookupTable instruction and derive the table address");
+    spec.post_encoded::<ExtendLookupTableRequest, InstructionsResponse>("/lut/extend", "Build an ExtendLookupTable instruction");
+
+    let body = spec.schema::<BuildTxRequest>();
+    let data = spec.schema::<BuildTxResponse>();
+    let params = spec.query_params::<FreshQuery>();
+    spec.add("post", "/tx/build", "Compile instructions into a transaction message", Some(body), params, data);
+    spec.post::<MessageBytesRequest, MessageBytesResponse>("/tx/message-bytes", "Return the exact message bytes signers sign over");
+    spec.post::<TxSizeRequest, TxSizeResponse>("/tx/size", "Compute the serialized size of a transaction and whether it fits in one packet");
+    spec.post::<TxSignersRequest, TxSignersResponse>("/tx/signers", "List the accounts that must sign a set of instructions");
+    spec.post::<SignTxRequest, SignTxResponse>("/tx/sign", "Sign a transaction message");
+    spec.post::<AttachSignaturesRequest, AttachSignaturesResponse>("/tx/attach-signatures", "Attach externally produced signatures to a message");
+    spec.post::<SendTxRequest, SendTxResponse>("/tx/send", "Submit a signed transaction");
+    spec.post::<SendTxRequest, SimulateTxResponse>("/tx/simulate", "Simulate a signed transaction");
+    spec.post_encoded::<SendTxRequest, DecodeTxResponse>("/tx/decode", "Decode a serialized transaction");
+
+    spec.get::<BlockhashQuery, BlockhashResponse>("/rpc/blockhash", "Fetch the latest blockhash");
+    let params = spec.query_params::<CommitmentQuery>();
+    let body = spec.schema::<FeeRequest>();
+    let data = spec.schema::<FeeResponse>();
+    spec.add("post", "/rpc/fee", "Fetch the fee the cluster would charge for a message", Some(body), params, data);
+    spec.post::<RentRequest, RentResponse>("/rpc/rent", "Fetch the rent-exempt minimum balance for an account size");
+    spec.post::<ConfirmTxRequest, ConfirmTxResponse>("/rpc/confirm", "Poll until a transaction reaches a commitment level");
+    let mut params = spec.query_params::<CommitmentQuery>();
+    params.insert(0, serde_json::json!({
+        "name": "pubkey", "in": "path", "required": true, "schema": { "type": "string" },
+    }));
+    let data = spec.schema::<BalanceResponse>();
+    spec.add("get", "/rpc/balance/{pubkey}", "Fetch an account's SOL balance", None, params, data);
+    spec.post::<DeriveAtaRequest, TokenBalanceResponse>("/rpc/token-balance", "Fetch an associated token account balance");
+
+    spec.post::<FindPdaRequest, FindPdaResponse>("/pda/find", "Find a program derived address");
+    spec.post::<ValidatePubkeyRequest, ValidatePubkeyResponse>("/pubkey/validate", "Validate a public key");
+    spec.post_encoded::<MemoRequest, InstructionResponse>("/memo", "Build an SPL Memo instruction");
+    spec.post_encoded::<ComputeUnitLimitRequest, InstructionResponse>("/compute-budget/limit", "Build a SetComputeUnitLimit instruction");
+    spec.post_encoded::<ComputeUnitPriceRequest, InstructionResponse>("/compute-budget/price", "Build a SetComputeUnitPrice instruction");
+    spec.post_encoded::<BatchRequest, InstructionsResponse>("/batch", "Build several instructions in one request");
+    spec.finish()
+}
+
+// --- Middleware ---
+
+const DEFAULT_REQUEST_TIMEOUT_MS: u64 = 10_000;
+const DEFAULT_CONFIRM_MAX_TIMEOUT_MS: u64 = 30_000;
+
+/// Per-request deadlines. Vanity search runs up to its own `timeoutMs`
+/// (capped at `MAX_VANITY_TIMEOUT_MS`), so it gets a separate, longer limit.
+/// Confirmation polling is capped at `confirm_max`, and its request deadline
+/// allows a second beyond that.
+#[derive(Clone, Copy)]
+struct RequestTimeouts {
+    default: Duration,
+    vanity: Duration,
+    confirm_max: Duration,
+}
+
+impl RequestTimeouts {
+    fn from_env() -> Self {
+        let read_ms = |var: &str, default: u64| {
+            env::var(var).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+        RequestTimeouts {
+            default: Duration::from_millis(read_ms("REQUEST_TIMEOUT_MS", DEFAULT_REQUEST_TIMEOUT_MS)),
+            vanity: Duration::from_millis(read_ms("VANITY_REQUEST_TIMEOUT_MS", MAX_VANITY_TIMEOUT_MS + 1_000)),
+            confirm_max: Duration::from_millis(read_ms("CONFIRM_MAX_TIMEOUT_MS", DEFAULT_CONFIRM_MAX_TIMEOUT_MS)),
+        }
+    }
+}
+
+/// Responds with 408 once a request exceeds its deadline, including time
+/// spent reading the body. The handler future is dropped at that point,
+/// cancelling any pending RPC call. Expects `RequestTimeouts` to be attached
+/// as request data.
+async fn enforce_timeout<E: Endpoint>(next: Arc<E>, req: Request) -> poem::Result<Response> {
+    let Some(timeouts) = req.data::<RequestTimeouts>().copied() else {
+        return Ok(next.get_response(req).await);
+    };
+    let limit = match req.uri().path() {
+        "/keypair/vanity" => timeouts.vanity,
+        "/rpc/confirm" => timeouts.confirm_max + Duration::from_secs(1),
+        _ => timeouts.default,
+    };
+    match tokio::time::timeout(limit, next.get_response(req)).await {
+        Ok(resp) => Ok(resp),
+        Err(_) => Ok(error(ApiError::Timeout(format!("Request timed out after {}ms", limit.as_millis())))
+            .into_response()),
+    }
+}
+
+const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Largest request body accepted, from `MAX_BODY_BYTES`.
+#[derive(Clone, Copy)]
+struct MaxBodyBytes(usize);
+
+impl MaxBodyBytes {
+    fn from_env() -> Self {
+        MaxBodyBytes(
+            env::var("MAX_BODY_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_BODY_BYTES),
+        )
+    }
+}
+
+/// Rejects oversized bodies with 413. A declared `Content-Length` is checked
+/// up front; chunked bodies are buffered only up to the limit. Expects
+/// `MaxBodyBytes` to be attached as request data.
+async fn limit_body_size<E: Endpoint>(next: Arc<E>, mut req: Request) -> poem::Result<Response> {
+    let Some(MaxBodyBytes(max)) = req.data::<MaxBodyBytes>().copied() else {
+        return Ok(next.get_response(req).await);
+    };
+    let too_large = || {
+        error(ApiError::PayloadTooLarge(max))
+            .into_response()
+    };
+    let declared = req
+        .header(header::CONTENT_LENGTH)
+        .and_then(|len| len.parse::<usize>().ok());
+    if declared.is_some_and(|len| len > max) {
+        return Ok(too_large());
+    }
+    match req.take_body().into_bytes_limit(max).await {
+        Ok(bytes) => req.set_body(bytes),
+        Err(ReadBodyError::PayloadTooLarge) => return Ok(too_large()),
+        Err(_) => return Ok(error(ApiError::InvalidInput("Failed to read request body".to_string())).into_response()),
+    }
+    Ok(next.get_response(req).await)
+}
+
+/// Whether the request declares a body, via a non-zero `Content-Length` or
+/// a `Transfer-Encoding` such as chunked.
+fn has_body(req: &Request) -> bool {
+    let declared = req.header(header::CONTENT_LENGTH).and_then(|len| len.trim().parse::<u64>().ok());
+    declared.is_some_and(|len| len > 0) || req.headers().contains_key(header::TRANSFER_ENCODING)
+}
+
+/// Rejects POST requests whose body is not declared as `application/json`
+/// with 415, so the error arrives in the usual `ApiResponse` envelope rather
+/// than poem's plain-text `Json` rejection. Bodyless POSTs such as `/keypair`
+/// and other methods pass through.
+async fn require_json<E: Endpoint>(next: Arc<E>, req: Request) -> poem::Result<Response> {
+    if req.method() == Method::POST && has_body(&req) {
+        let content_type = req.header(header::CONTENT_TYPE).unwrap_or_default();
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        if !essence.eq_ignore_ascii_case("application/json") {
+            let got = if content_type.is_empty() { "none" } else { content_type };
+            return Ok(error(ApiError::UnsupportedMediaType(got.to_string())).into_response());
+        }
+    }
+    Ok(next.get_response(req).await)
+}
+
+const DEFAULT_COMPRESSION_MIN_BYTES: usize = 1024;
+
+/// Smallest response body worth compressing, from `COMPRESSION_MIN_BYTES`.
+#[derive(Clone, Copy)]
+struct CompressionMinBytes(usize);
+
+impl CompressionMinBytes {
+    fn from_env() -> Self {
+        CompressionMinBytes(
+            env::var("COMPRESSION_MIN_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_COMPRESSION_MIN_BYTES),
+        )
+    }
+}
+
+/// Picks the client's preferred coding from `Accept-Encoding`, skipping
+/// `q=0` entries. Equal weights favour br, then gzip, then deflate.
+fn negotiate_encoding(req: &Request) -> Option<CompressionAlgo> {
+    req.header(header::ACCEPT_ENCODING)?
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let (algo, rank) = match parts.next()?.trim().to_ascii_lowercase().as_str() {
+                "br" => (CompressionAlgo::BR, 3),
+                "gzip" => (CompressionAlgo::GZIP, 2),
+                "deflate" => (CompressionAlgo::DEFLATE, 1),
+                _ => return None,
+            };
+            let q = match parts.find_map(|p| p.trim().strip_prefix("q=")) {
+                Some(q) => q.trim().parse::<f32>().ok()?,
+                None => 1.0,
+            };
+            (q > 0.0).then_some((q, rank, algo))
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+        .map(|(_, _, algo)| algo)
+}
+
+/// Compresses responses of at least `CompressionMinBytes` with the coding
+/// negotiated from `Accept-Encoding`; smaller bodies are sent as-is.
+/// Expects `CompressionMinBytes` to be attached as request data.
+async fn compress_response<E: Endpoint>(next: Arc<E>, req: Request) -> poem::Result<Response> {
+    let min = req.data::<CompressionMinBytes>().copied();
+    let algo = negotiate_encoding(&req);
+    let resp = next.get_response(req).await;
+    let (Some(CompressionMinBytes(min)), Some(algo)) = (min, algo) else {
+        return Ok(resp);
+    };
+    if resp.headers().contains_key(header::CONTENT_ENCODING)
+        || matches!(resp.status(), StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED)
+    {
+        return Ok(resp);
+    }
+    let (mut parts, body) = resp.into_parts();
+    parts.headers.append(header::VARY, header::HeaderValue::from_static("accept-encoding"));
+    let bytes = body.into_vec().await.unwrap_or_default();
+    let compress = bytes.len() >= min;
+    let resp = Response::from_parts(parts, Body::from(bytes));
+    if !compress {
+        return Ok(resp);
+    }
+    Ok(Compress::new(resp, algo).into_response())
+}
+
+/// Prometheus collectors, labeled by the matched route pattern so that path
+/// parameters such as `/rpc/balance/:pubkey` do not explode cardinality.
+///
+/// - `http_requests_total{endpoint,method,status}`: requests served
+/// - `http_request_duration_seconds{endpoint}`: request latency histogram
+/// - `http_request_errors_total{endpoint}`: responses with a 4xx/5xx status
+struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    latency: HistogramVec,
+    errors: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new();
+        let requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "Total HTTP requests by endpoint, method and status"),
+            &["endpoint", "method", "status"],
+        )?;
+        let latency = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "HTTP request latency in seconds by endpoint"),
+            &["endpoint"],
+        )?;
+        let errors = IntCounterVec::new(
+            Opts::new("http_request_errors_total", "HTTP responses with a 4xx or 5xx status by endpoint"),
+            &["endpoint"],
+        )?;
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(latency.clone()))?;
+        registry.register(Box::new(errors.clone()))?;
+        Ok(Metrics { registry, requests, latency, errors })
+    }
+}
+
+/// Records request count, latency and errors for every request, including
+/// ones rejected before routing (labelled `unmatched`). Expects `Arc<Metrics>` to be attached as request data.
+async fn track_metrics<E: Endpoint>(next: Arc<E>, req: Request) -> poem::Result<Response> {
+    let metrics = req.data::<Arc<Metrics>>().cloned();
+    let method = req.method().to_string();
+    let start = Instant::now();
+    let resp = next.get_response(req).await;
+    if let Some(metrics) = metrics {
+        let endpoint = resp
+            .data::<PathPattern>()
+            .map(|p| p.0.to_string())
+            .unwrap_or_else(|| "unmatched".to_string());
+        let status = resp.status();
+        metrics
+            .requests
+            .with_label_values(&[&endpoint, &method, status.as_str()])
+            .inc();
+        metrics
+            .latency
+            .with_label_values(&[&endpoint])
+            .observe(start.elapsed().as_secs_f64());
+        if status.is_client_error() || status.is_server_error() {
+            metrics.errors.with_label_values(&[&endpoint]).inc();
+        }
+    }
+    Ok(resp)
+}
+
+const DEFAULT_RATE_LIMIT_PER_MINUTE: u32 = 60;
+/// How often buckets that have refilled completely are dropped.
+const RATE_LIMIT_PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket per client IP: each client may burst up to `capacity`
+/// requests, refilled continuously at `capacity` tokens per minute.
+struct RateLimiter {
+    capacity: f64,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    /// A limit of 0 would reject every request, so it is refused here rather
+    /// than at the first request.
+    fn new(requests_per_minute: u32) -> Result<Self, String> {
+        if requests_per_minute == 0 {
+            return Err("RATE_LIMIT_PER_MINUTE must be at least 1".to_string());
+        }
+        Ok(RateLimiter { capacity: requests_per_minute as f64, buckets: Mutex::new(HashMap::new()) })
+    }
+
+    fn from_env() -> Result<Self, String> {
+        let limit = env::var("RATE_LIMIT_PER_MINUTE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_RATE_LIMIT_PER_MINUTE);
+        Self::new(limit)
+    }
+
+    fn try_acquire(&self, ip: IpAddr) -> bool {
+        let now = Instant::now();
+        let refill_per_sec = self.capacity / 60.0;
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = buckets.entry(ip).or_insert(Bucket { tokens: self.capacity, updated: now });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(self.capacity);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Drops buckets that have refilled to capacity; a fresh bucket for that
+    /// client would be identical.
+    fn prune(&self) {
+        let now = Instant::now();
+        let refill_per_sec = self.capacity / 60.0;
+        let capacity = self.capacity;
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        buckets.retain(|_, b| b.tokens + now.duration_since(b.updated).as_secs_f64() * refill_per_sec < capacity);
+    }
+
+    /// Prunes idle buckets every `RATE_LIMIT_PRUNE_INTERVAL` for the lifetime
+    /// of the process, keeping `try_acquire` constant-time.
+    fn spawn_prune(self: &Arc<Self>) {
+        let limiter = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RATE_LIMIT_PRUNE_INTERVAL);
+            loop {
+                interval.tick().await;
+                limiter.prune();
+            }
+        });
+    }
+}
+
+/// Rejects clients that exceed their rate limit with 429. `/health` and
+/// `/health/live` are exempt; `/health/ready` calls RPC and is limited like
+/// any other route. Expects `Arc<RateLimiter>` to be attached as request data.
+async fn rate_limit<E: Endpoint>(next: Arc<E>, req: Request) -> poem::Result<Response> {
+    if !matches!(req.uri().path(), "/health" | "/health/live") {
+        let ip = req.remote_addr().as_socket_addr().map(|addr| addr.ip());
+        if let (Some(limiter), Some(ip)) = (req.data::<Arc<RateLimiter>>(), ip) {
+            if !limiter.try_acquire(ip) {
+                return Ok(error(ApiError::RateLimited).into_response());
+            }
+        }
+    }
+    Ok(next.get_response(req).await)
+}
+
+/// Key required in the `x-api-key` header when `API_KEY` is set.
+#[derive(Clone)]
+struct ApiKey(Option<String>);
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Rejects requests without a matching `x-api-key` header with 401 when an
+/// API key is configured. The `/health` routes and `/version` are always open.
+async fn require_api_key<E: Endpoint>(next: Arc<E>, req: Request) -> poem::Result<Response> {
+    if let Some(ApiKey(Some(expected))) = req.data::<ApiKey>() {
+        if !matches!(req.uri().path(), "/health" | "/health/live" | "/health/ready" | "/version") {
+            let provided = req.header("x-api-key").unwrap_or_default();
+            if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+                return Ok(error(ApiError::Unauthorized).into_response());
+            }
+        }
+    }
+    Ok(next.get_response(req).await)
+}
+
+/// Builds the CORS middleware from a comma-separated origin list, where `*`
+/// allows any origin.
+fn build_cors(origins: &str) -> Cors {
+    let cors = Cors::new()
+        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_headers(["content-type", "x-api-key", REQUEST_ID_HEADER])
+        .expose_header(REQUEST_ID_HEADER);
+    if origins.trim() == "*" {
+        return cors;
+    }
+    cors.allow_origins(origins.split(',').map(str::trim).filter(|o| !o.is_empty()))
+}
+
+/// `Cors` answers preflight requests with 200; report them as 204 No Content.
+async fn preflight_no_content<E: Endpoint>(next: Arc<E>, req: Request) -> poem::Result<Response> {
+    let is_preflight = req.method() == Method::OPTIONS
+        && req.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD);
+    let mut resp = next.get_response(req).await;
+    if is_preflight
+        && resp.status() == StatusCode::OK
+        && resp.headers().contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+    {
+        resp.set_status(StatusCode::NO_CONTENT);
+    }
+    Ok(resp)
+}
+
+const REQUEST_ID_HEADER: &str = "x-request-id";
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Reuses the client's `x-request-id` when it is short printable ASCII,
+/// otherwise generates a fresh UUID.
+fn request_id(req: &Request) -> String {
+    req.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic()))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+/// Logs method, path, status and latency of every request inside a span
+/// tagged with its request id. The id is echoed in the `x-request-id`
+/// header and as `requestId` in `ApiResponse` bodies. Failed requests also
+/// log the `error` message from their `ApiResponse` body.
+async fn log_request<E: Endpoint>(next: Arc<E>, req: Request) -> poem::Result<Response> {
+    let request_id = request_id(&req);
+    let span = tracing::info_span!(
+        "request",
+        %request_id,
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
+    async move {
+        let start = Instant::now();
+        let resp = next.get_response(req).await;
+        let status = resp.status();
+        let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+        let (mut parts, body) = resp.into_parts();
+        if let Ok(value) = header::HeaderValue::from_str(&request_id) {
+            parts.headers.insert(REQUEST_ID_HEADER, value);
+        }
+        let is_json = parts
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("application/json"));
+        let failed = status.is_client_error() || status.is_server_error();
+        if !is_json && !failed {
+            tracing::info!(status = status.as_u16(), latency_ms, "request completed");
+            return Ok(Response::from_parts(parts, body));
+        }
+        let mut bytes = body.into_vec().await.unwrap_or_default();
+        let mut json = serde_json::from_slice::<serde_json::Value>(&bytes).ok();
+        // Only `ApiResponse` envelopes carry the id; other JSON such as the
+        // OpenAPI document passes through untouched.
+        if let Some(obj) = json.as_mut().and_then(|v| v.as_object_mut()).filter(|o| o.contains_key("success")) {
+            obj.insert("requestId".to_string(), request_id.clone().into());
+            if let Ok(rewritten) = serde_json::to_vec(obj) {
+                bytes = rewritten;
+                parts.headers.remove(header::CONTENT_LENGTH);
+            }
+        }
+        if !failed {
+            tracing::info!(status = status.as_u16(), latency_ms, "request completed");
+            return Ok(Response::from_parts(parts, Body::from(bytes)));
+        }
+        let message = json
+            .as_ref()
+            .and_then(|v| v.get("error").and_then(|e| e.as_str()).map(str::to_string))
+            .unwrap_or_else(|| String::from_utf8_lossy(&bytes).into_owned());
+        tracing::info!(status = status.as_u16(), latency_ms, error = %message, "request failed");
+        Ok(Response::from_parts(parts, Body::from(bytes)))
+    }
+    .instrument(span)
+    .await
+}
+
+/// Wraps poem's `Json` extractor rejections in the `ApiResponse` envelope.
+/// serde's message names the offending field where it can, e.g.
+/// "missing field `mint` at line 1 column 2".
+async fn json_rejection(err: ParseJsonError) -> (StatusCode, Json<ApiResponse>) {
+    let message = match err {
+        ParseJsonError::Parse(e) => format!("Invalid JSON body: {e}"),
+        other => other.to_string(),
+    };
+    error(ApiError::InvalidInput(message))
+}
+
+/// Turns a handler panic into a 500 `ApiResponse` instead of a dropped
+/// connection. The payload is logged but never sent to the client.
+fn panic_response(payload: Box<dyn std::any::Any + Send + 'static>) -> (StatusCode, Json<ApiResponse>) {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string());
+    tracing::error!(panic = %message, "handler panicked");
+    error(ApiError::Internal("Internal server error".to_string()))
+}
+
+/// How long in-flight requests may keep running after a shutdown signal.
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// Resolves on Ctrl+C or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("failed to listen for ctrl-c: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("failed to listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    tracing::info!("shutdown signal received, draining in-flight requests");
+}
+
+#[derive(Parser)]
+#[command(version, about = "Solana HTTP API server")]
+struct Cli {
+    /// Address to bind
+    #[arg(long, env = "HOST", default_value = "0.0.0.0")]
+    host: String,
+    /// Port to listen on
+    #[arg(long, env = "PORT", default_value_t = 3000)]
+    port: u16,
+    /// Solana JSON-RPC endpoint used by the /tx and /rpc routes
+    #[arg(long, env = "RPC_URL", default_value = DEFAULT_RPC_URL)]
+    rpc_url: String,
+    /// Default commitment for RPC calls without an explicit one
+    #[arg(long, env = "COMMITMENT", default_value = "confirmed", value_parser = ["processed", "confirmed", "finalized"])]
+    commitment: String,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), std::io::Error> {
+    let health_state = Arc::new(HealthState::new());
+    dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .init();
+    let cli = Cli::parse();
+    let addr = format!("{}:{}", cli.host, cli.port);
+    let metrics = Arc::new(Metrics::new().map_err(std::io::Error::other)?);
+    let limiter = Arc::new(RateLimiter::from_env().map_err(std::io::Error::other)?);
+    limiter.spawn_prune();
+    let timeouts = RequestTimeouts::from_env();
+    let max_body = MaxBodyBytes::from_env();
+    let compression_min = CompressionMinBytes::from_env();
+    let commitment = parse_commitment(Some(&cli.commitment), CommitmentConfig::confirmed())
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    // One client for the whole process so its HTTP connection pool is shared.
+    let rpc_client = Arc::new(RpcClient::new_with_commitment(cli.rpc_url.clone(), commitment));
+    let blockhash_cache = Arc::new(BlockhashCache::from_env());
+    let blockhash_ttl = blockhash_cache.ttl;
+    blockhash_cache.spawn_refresh(Arc::clone(&rpc_client));
+    let api_key = ApiKey(env::var("API_KEY").ok().filter(|key| !key.is_empty()));
+    let cors_origins = env::var("CORS_ALLOWED_ORIGINS").ok().filter(|origins| !origins.is_empty());
+    let rate_limit_per_minute = limiter.capacity;
+    let api_key_required = api_key.0.is_some();
+    let app = Route::new()
+        .at("/health", health)
+        .at("/health/live", health_live)
+        .at("/health/ready", health_ready)
+        .at("/version", service_version)
+        .at("/metrics", render_metrics)
+        .at("/openapi.json", openapi_json)
+        .at("/docs", swagger_ui)
+        .at("/keypair", generate_keypair)
+        .at("/keypair/batch", generate_keypair_batch)
+        .at("/keypair/import", import_keypair)
+        .at("/keypair/from-seed", keypair_from_seed_bytes)
+        .at("/keypair/from-mnemonic", keypair_from_mnemonic)
+        .at("/keypair/vanity", vanity_keypair)
+        .at("/token/create", create_token)
+        .at("/token/create-mint-full", create_mint_full)
         .at("/token/mint", mint_token)
+        .at("/token/mint/checked", mint_token_checked)
+        .at("/token/burn", burn_token)
+        .at("/token/burn/checked", burn_token_checked)
+        .at("/token/close", close_account)
+        .at("/token/approve", approve)
+        .at("/token/revoke", revoke)
+        .at("/token/freeze", freeze_account)
+        .at("/token/thaw", thaw_account)
+        .at("/token/set-authority", set_authority)
+        .at("/token/account/init", initialize_account)
+        .at("/token/account/create-full", create_token_account_full)
+        .at("/token/multisig/create", initialize_multisig)
+        .at("/token/decode", decode_token_ix)
+        .at("/token/ata/derive", derive_ata)
+        .at("/token/transfer-fee/calc", transfer_fee)
+        .at("/token/amount/convert", convert_amount)
+        .at("/token/ata/create", create_ata)
+        .at("/token/wrap", wrap_sol)
+        .at("/token/unwrap", unwrap_sol)
+        .at("/solana-pay/transfer-url", solana_pay_transfer_url)
+        .at("/solana-pay/parse", solana_pay_parse)
         .at("/message/sign", sign_message)
+        .at("/message/sign/batch", sign_message_batch)
         .at("/message/verify", verify_message)
+        .at("/message/verify/batch", verify_message_batch)
+        .at("/message/verify/multi", verify_message_multi)
+        .at("/message/siws/build", build_siws_message)
+        .at("/message/siws/verify", verify_siws_message)
         .at("/send/sol", send_sol)
-        .at("/send/token", send_token);
-    println!("🚀 Solana HTTP Server starting on {}", addr);
+        .at("/send/sol/with-seed", send_sol_with_seed)
+        .at("/send/sol/multi", send_sol_multi)
+        .at("/send/token", send_token)
+        .at("/send/token/checked", send_token_checked)
+        .at("/send/token/multi", send_token_multi)
+        .at("/system/create-account", create_account)
+        .at("/system/assign", assign_account)
+        .at("/system/allocate", allocate_account)
+        .at("/nonce/create", create_nonce)
+        .at("/nonce/advance", advance_nonce)
+        .at("/nonce/withdraw", withdraw_nonce)
+        .at("/stake/create", create_stake)
+        .at("/stake/delegate", delegate_stake)
+        .at("/stake/deactivate", deactivate_stake)
+        .at("/stake/withdraw", withdraw_stake)
+        .at("/lut/create", create_lookup_table)
+        .at("/lut/extend", extend_lookup_table)
+        .at("/tx/build", build_tx)
+        .at("/tx/message-bytes", message_bytes)
+        .at("/tx/size", tx_size)
+        .at("/tx/signers", tx_signers)
+        .at("/tx/sign", sign_tx)
+        .at("/tx/attach-signatures", attach_signatures)
+        .at("/tx/send", send_tx)
+        .at("/tx/simulate", simulate_tx)
+        .at("/tx/decode", decode_tx)
+        .at("/rpc/blockhash", rpc_blockhash)
+        .at("/rpc/fee", rpc_fee)
+        .at("/rpc/rent", rpc_rent)
+        .at("/rpc/confirm", rpc_confirm)
+        .at("/rpc/balance/:pubkey", rpc_balance)
+        .at("/rpc/token-balance", rpc_token_balance)
+        .at("/pda/find", find_pda)
+        .at("/pubkey/validate", validate_pubkey)
+        .at("/memo", memo)
+        .at("/compute-budget/limit", compute_unit_limit)
+        .at("/compute-budget/price", compute_unit_price)
+        .at("/batch", batch)
+        .with(CatchPanic::new().with_handler(panic_response))
+        .catch_error(json_rejection)
+        .data(rpc_client)
+        .data(health_state)
+        .data(blockhash_cache)
+        .around(require_json)
+        .boxed()
+        // Each `around` layer is boxed; nesting the generic middleware fns
+        // otherwise overflows the compiler's type recursion limit.
+        .around(limit_body_size)
+        .boxed()
+        .data(max_body)
+        // Outside the body limit so a slow upload being buffered still times out.
+        .around(enforce_timeout)
+        .boxed()
+        .data(timeouts)
+        .around(require_api_key)
+        .boxed()
+        .data(api_key)
+        .around(rate_limit)
+        .boxed()
+        .data(limiter)
+        // Outside auth and rate limiting so 401s and 429s are counted too.
+        .around(track_metrics)
+        .boxed()
+        .data(metrics)
+        .with_if(cors_origins.is_some(), build_cors(cors_origins.as_deref().unwrap_or_default()))
+        .around(preflight_no_content)
+        .boxed()
+        .around(log_request)
+        .boxed()
+        .around(compress_response)
+        .boxed()
+        .data(compression_min);
+    println!("🚀 Solana HTTP Server starting");
+    println!("   listen:     {}", addr);
+    println!("   rpc url:    {} ({})", cli.rpc_url, cli.commitment);
+    println!("   rate limit: {} requests/minute per IP", rate_limit_per_minute);
+    println!("   api key:    {}", if api_key_required { "required" } else { "disabled" });
+    println!("   cors:       {}", cors_origins.as_deref().unwrap_or("disabled"));
+    println!(
+        "   timeout:    {}ms (vanity {}ms, confirm {}ms)",
+        timeouts.default.as_millis(),
+        timeouts.vanity.as_millis(),
+        timeouts.confirm_max.as_millis()
+    );
+    println!("   max body:   {} bytes", max_body.0);
+    println!("   compress:   responses >= {} bytes", compression_min.0);
+    println!("   blockhash:  cached for {}ms", blockhash_ttl.as_millis());
     Server::new(TcpListener::bind(addr))
-        .run(app)
-        .await
+        .run_with_graceful_shutdown(app, shutdown_signal(), Some(SHUTDOWN_GRACE_PERIOD))
+        .await?;
+    tracing::info!("server shut down");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body_json(resp: Response) -> serde_json::Value {
+        let bytes = resp.into_body().into_vec().await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    async fn post_json(ep: impl Endpoint, path: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri_str(path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.to_string());
+        let resp = ep.get_response(req).await;
+        (resp.status(), body_json(resp).await)
+    }
+
+    #[tokio::test]
+    async fn bodyless_post_skips_content_type_check() {
+        let app = Route::new().at("/keypair", generate_keypair).around(require_json);
+        let req = Request::builder().method(Method::POST).uri_str("/keypair").finish();
+        let resp = app.get_response(req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(body_json(resp).await["success"], true);
+    }
+
+    #[tokio::test]
+    async fn non_json_body_is_rejected_with_415() {
+        let app = Route::new().at("/token/create", create_token).around(require_json);
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri_str("/token/create")
+            .header(header::CONTENT_TYPE, "text/plain")
+            .header(header::CONTENT_LENGTH, "2")
+            .body("{}");
+        let resp = app.get_response(req).await;
+        assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let body = body_json(resp).await;
+        assert_eq!(body["success"], false);
+        assert_eq!(body["errorCode"], "UNSUPPORTED_MEDIA_TYPE");
+    }
+
+    #[test]
+    fn send_token_source_is_first_account() {
+        let source = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let req = SendTokenRequest {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            mint: Pubkey::new_unique().to_string(),
+            owner: Keypair::new().pubkey().to_string(),
+            amount: 5,
+        };
+        let ix = build_send_token(&req).unwrap();
+        assert_eq!(ix.accounts[0].pubkey, source);
+        assert_eq!(ix.accounts[1].pubkey, destination);
+    }
+
+    #[tokio::test]
+    async fn send_token_reports_signer_and_writable_flags() {
+        let source = Pubkey::new_unique().to_string();
+        let destination = Pubkey::new_unique().to_string();
+        let owner = Keypair::new().pubkey().to_string();
+        let app = Route::new().at("/send/token", send_token);
+        let (status, body) = post_json(app, "/send/token", serde_json::json!({
+            "source": source,
+            "destination": destination,
+            "mint": Pubkey::new_unique().to_string(),
+            "owner": owner,
+            "amount": 5,
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+        let accounts = body["data"]["accounts"].as_array().unwrap();
+        assert_eq!(accounts[0]["pubkey"], source);
+        assert_eq!(accounts[0]["isWritable"], true);
+        assert_eq!(accounts[1]["pubkey"], destination);
+        assert_eq!(accounts[1]["isWritable"], true);
+        assert_eq!(accounts[2]["pubkey"], owner);
+        assert_eq!(accounts[2]["isSigner"], true);
+    }
+
+    #[tokio::test]
+    async fn verify_message_rejects_short_signature() {
+        let app = Route::new().at("/message/verify", verify_message);
+        let (status, body) = post_json(app, "/message/verify", serde_json::json!({
+            "message": "hello",
+            "signature": general_purpose::STANDARD.encode([7u8; 10]),
+            "pubkey": Pubkey::new_unique().to_string(),
+        })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["errorCode"], "INVALID_SIGNATURE");
+    }
+
+    #[tokio::test]
+    async fn send_sol_rejects_self_transfer() {
+        let wallet = Keypair::new().pubkey().to_string();
+        let app = Route::new().at("/send/sol", send_sol);
+        let (status, body) = post_json(app, "/send/sol", serde_json::json!({
+            "from": wallet,
+            "to": wallet,
+            "lamports": 1000,
+        })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Source and destination must differ");
+    }
+
+    #[tokio::test]
+    async fn burn_token_returns_token_program_and_three_accounts() {
+        let account = Pubkey::new_unique().to_string();
+        let mint = Pubkey::new_unique().to_string();
+        let owner = Keypair::new().pubkey().to_string();
+        let app = Route::new().at("/token/burn", burn_token);
+        let (status, body) = post_json(app, "/token/burn", serde_json::json!({
+            "account": account,
+            "mint": mint,
+            "owner": owner,
+            "amount": 10,
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["programId"], spl_token::id().to_string());
+        let accounts = body["data"]["accounts"].as_array().unwrap();
+        assert_eq!(accounts.len(), 3);
+        assert_eq!(accounts[0]["pubkey"], account);
+        assert_eq!(accounts[1]["pubkey"], mint);
+        assert_eq!(accounts[2]["pubkey"], owner);
+        assert_eq!(accounts[2]["isSigner"], true);
+    }
+
+    #[tokio::test]
+    async fn close_account_destination_writable_and_owner_signs() {
+        let destination = Pubkey::new_unique().to_string();
+        let owner = Keypair::new().pubkey().to_string();
+        let app = Route::new().at("/token/close", close_account);
+        let (status, body) = post_json(app, "/token/close", serde_json::json!({
+            "account": Pubkey::new_unique().to_string(),
+            "destination": destination,
+            "owner": owner,
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+        let accounts = body["data"]["accounts"].as_array().unwrap();
+        assert_eq!(accounts[1]["pubkey"], destination);
+        assert_eq!(accounts[1]["isWritable"], true);
+        assert_eq!(accounts[2]["pubkey"], owner);
+        assert_eq!(accounts[2]["isSigner"], true);
+    }
+
+    #[tokio::test]
+    async fn instruction_responses_use_camel_case_keys() {
+        let mint = Pubkey::new_unique().to_string();
+        let owner = Keypair::new().pubkey().to_string();
+        let cases = [
+            ("/token/create", serde_json::json!({ "mintAuthority": owner, "mint": mint, "decimals": 6 })),
+            ("/token/mint", serde_json::json!({
+                "mint": mint,
+                "destination": Pubkey::new_unique().to_string(),
+                "authority": owner,
+                "amount": 5,
+            })),
+            ("/send/sol", serde_json::json!({ "from": owner, "to": Pubkey::new_unique().to_string(), "lamports": 5 })),
+            ("/send/token", serde_json::json!({
+                "source": Pubkey::new_unique().to_string(),
+                "destination": Pubkey::new_unique().to_string(),
+                "mint": mint,
+                "owner": owner,
+                "amount": 5,
+            })),
+            ("/token/ata/create", serde_json::json!({ "funder": owner, "owner": owner, "mint": mint })),
+        ];
+        for (path, request) in cases {
+            let app = Route::new()
+                .at("/token/create", create_token)
+                .at("/token/mint", mint_token)
+                .at("/send/sol", send_sol)
+                .at("/send/token", send_token)
+                .at("/token/ata/create", create_ata);
+            let (status, body) = post_json(app, path, request).await;
+            assert_eq!(status, StatusCode::OK, "{path}: {body}");
+            let data = body["data"].as_object().unwrap();
+            assert!(data.contains_key("programId") && data.contains_key("instructionData"), "{path}: {body}");
+            assert!(!data.contains_key("program_id") && !data.contains_key("instruction_data"), "{path}: {body}");
+            if path == "/token/ata/create" {
+                assert!(data.contains_key("ata"), "{body}");
+            }
+            // `/send/sol` lists plain pubkeys; the others carry signer/writable flags.
+            if let Some(meta) = data["accounts"][0].as_object() {
+                assert!(meta.contains_key("isSigner") && meta.contains_key("isWritable"), "{path}: {body}");
+                assert!(!meta.contains_key("is_signer") && !meta.contains_key("is_writable"), "{path}: {body}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn sign_message_encodings_decode_to_same_signature() {
+        let keypair = Keypair::new();
+        let app = Route::new().at("/message/sign", sign_message);
+        let (status, body) = post_json(app, "/message/sign", serde_json::json!({
+            "message": "hello",
+            "secret": keypair.to_base58_string(),
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+        let from_base58 = decode_signature(body["data"]["signatureBase58"].as_str().unwrap()).unwrap();
+        let from_base64 = decode_signature(body["data"]["signatureBase64"].as_str().unwrap()).unwrap();
+        assert_eq!(from_base58.as_ref().len(), 64);
+        assert_eq!(from_base58, from_base64);
+        assert_eq!(from_base58, keypair.sign_message(b"hello"));
+    }
+
+    #[test]
+    fn secret_bytes_accepts_seed_and_rejects_bad_length() {
+        let keypair = Keypair::new();
+        let from_seed = keypair_from_secret_bytes(&keypair.secret().to_bytes()).unwrap();
+        assert_eq!(from_seed.pubkey(), keypair.pubkey());
+
+        let err = keypair_from_secret_bytes(&[1u8; 65]).unwrap_err();
+        assert!(matches!(err, ApiError::SecretLength(65)));
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn sign_message_reports_secret_length() {
+        let secret = [1u8; 65].to_base58();
+        let app = Route::new().at("/message/sign", sign_message);
+        let (status, body) = post_json(app, "/message/sign", serde_json::json!({
+            "message": "hello",
+            "secret": secret,
+        })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Secret key must be 32 or 64 bytes, got 65");
+    }
+
+    #[handler]
+    async fn always_panics() -> &'static str {
+        panic!("forced failure")
+    }
+
+    #[tokio::test]
+    async fn handler_panic_becomes_500_api_response() {
+        let app = Route::new()
+            .at("/panic", always_panics)
+            .with(CatchPanic::new().with_handler(panic_response));
+        let req = Request::builder().uri_str("/panic").finish();
+        let resp = app.get_response(req).await;
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = body_json(resp).await;
+        assert_eq!(body["success"], false);
+        assert_eq!(body["errorCode"], "INTERNAL_ERROR");
+        assert_eq!(body["error"], "Internal server error");
+    }
+
+    #[tokio::test]
+    async fn empty_body_returns_structured_400() {
+        let app = Route::new().at("/token/create", create_token).catch_error(json_rejection);
+        let (status, body) = post_json(app, "/token/create", serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["success"], false);
+        assert_eq!(body["errorCode"], "INVALID_INPUT");
+        let message = body["error"].as_str().unwrap();
+        assert!(message.starts_with("Invalid JSON body: missing field"), "{message}");
+    }
+
+    async fn get_with_encoding(ep: impl Endpoint, path: &str, accept_encoding: Option<&str>) -> Response {
+        let mut req = Request::builder().uri_str(path);
+        if let Some(accept_encoding) = accept_encoding {
+            req = req.header(header::ACCEPT_ENCODING, accept_encoding);
+        }
+        ep.get_response(req.finish()).await
+    }
+
+    fn gunzip(bytes: &[u8]) -> Vec<u8> {
+        use std::io::Read;
+        let mut out = Vec::new();
+        flate2::read::GzDecoder::new(bytes).read_to_end(&mut out).unwrap();
+        out
+    }
+
+    #[tokio::test]
+    async fn large_json_response_is_gzipped() {
+        let min = CompressionMinBytes(DEFAULT_COMPRESSION_MIN_BYTES);
+        let app = || Route::new().at("/openapi.json", openapi_json).around(compress_response).data(min);
+
+        let plain = get_with_encoding(app(), "/openapi.json", None).await;
+        assert!(plain.header(header::CONTENT_ENCODING).is_none());
+        let plain = plain.into_body().into_vec().await.unwrap();
+        assert!(plain.len() >= min.0);
+
+        let resp = get_with_encoding(app(), "/openapi.json", Some("gzip")).await;
+        assert_eq!(resp.header(header::CONTENT_ENCODING), Some("gzip"));
+        let compressed = resp.into_body().into_vec().await.unwrap();
+        assert!(compressed.len() < plain.len());
+        assert_eq!(gunzip(&compressed), plain);
+    }
+
+    #[tokio::test]
+    async fn metrics_response_is_gzipped() {
+        let metrics = Arc::new(Metrics::new().unwrap());
+        for endpoint in ["/keypair", "/token/create", "/send/sol", "/send/token", "/message/sign"] {
+            metrics.requests.with_label_values(&[endpoint, "POST", "200"]).inc();
+            metrics.latency.with_label_values(&[endpoint]).observe(0.01);
+        }
+        let min = CompressionMinBytes(DEFAULT_COMPRESSION_MIN_BYTES);
+        let app = || {
+            Route::new()
+                .at("/metrics", render_metrics)
+                .data(Arc::clone(&metrics))
+                .around(compress_response)
+                .data(min)
+        };
+
+        let plain = get_with_encoding(app(), "/metrics", None).await.into_body().into_vec().await.unwrap();
+        assert!(plain.len() >= min.0);
+
+        let resp = get_with_encoding(app(), "/metrics", Some("deflate;q=0.5, gzip")).await;
+        assert_eq!(resp.header(header::CONTENT_ENCODING), Some("gzip"));
+        let compressed = resp.into_body().into_vec().await.unwrap();
+        assert_eq!(gunzip(&compressed), plain);
+    }
+
+    #[tokio::test]
+    async fn small_response_is_not_compressed() {
+        let app = Route::new()
+            .at("/health/live", health_live)
+            .around(compress_response)
+            .data(CompressionMinBytes(DEFAULT_COMPRESSION_MIN_BYTES));
+        let resp = get_with_encoding(app, "/health/live", Some("gzip, br")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.header(header::CONTENT_ENCODING).is_none());
+        assert_eq!(body_json(resp).await["success"], true);
+    }
+
+    /// `/keypair/batch` as it would be with key generation left on the async
+    /// workers instead of `spawn_blocking`.
+    #[handler]
+    async fn generate_keypair_batch_inline(Json(req): Json<KeypairBatchRequest>) -> (StatusCode, Json<ApiResponse>) {
+        let pubkeys: Vec<String> = (0..req.count).map(|_| Keypair::new().pubkey().to_string()).collect();
+        success(serde_json::json!({ "pubkeys": pubkeys }))
+    }
+
+    /// Fires a burst of maximum-size `/keypair/batch` requests at `app` while a
+    /// probe on the same runtime sleeps 1ms and then calls `/health/live`;
+    /// returns the p99 of the probe's overshoot, i.e. how long it waited for a
+    /// worker.
+    async fn probe_p99_under_keypair_load(app: Route) -> Duration {
+        const REQUESTS: usize = 200;
+        const PROBE_INTERVAL: Duration = Duration::from_millis(1);
+        let app = Arc::new(app.at("/health/live", health_live));
+        let done = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let probe = {
+            let (app, done) = (Arc::clone(&app), Arc::clone(&done));
+            tokio::spawn(async move {
+                let mut samples = Vec::new();
+                // Checked after sampling, so there is always at least one sample.
+                loop {
+                    let start = Instant::now();
+                    tokio::time::sleep(PROBE_INTERVAL).await;
+                    app.get_response(Request::builder().uri_str("/health/live").finish()).await;
+                    samples.push(start.elapsed().saturating_sub(PROBE_INTERVAL));
+                    if done.load(std::sync::atomic::Ordering::Relaxed) {
+                        break samples;
+                    }
+                }
+            })
+        };
+        // Let the probe start before the load queues up ahead of it.
+        tokio::time::sleep(PROBE_INTERVAL * 10).await;
+        let load: Vec<_> = (0..REQUESTS)
+            .map(|_| {
+                let app = Arc::clone(&app);
+                tokio::spawn(async move {
+                    let req = Request::builder()
+                        .method(Method::POST)
+                        .uri_str("/keypair/batch")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(serde_json::json!({ "count": MAX_KEYPAIR_BATCH }).to_string());
+                    app.get_response(req).await.status()
+                })
+            })
+            .collect();
+        for handle in load {
+            assert_eq!(handle.await.unwrap(), StatusCode::OK);
+        }
+        done.store(true, std::sync::atomic::Ordering::Relaxed);
+        let mut samples = probe.await.unwrap();
+        samples.sort_unstable();
+        samples[(samples.len() - 1) * 99 / 100]
+    }
+
+    /// Compares probe tail latency under keypair generation load with the
+    /// work offloaded (the real handler) against generating inline on the
+    /// workers. A single `/keypair` is too cheap for the difference to rise
+    /// above `spawn_blocking` overhead, hence the batch route. Run with
+    /// `cargo test --release -- --ignored keypair_load`.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[ignore]
+    async fn keypair_load_keeps_runtime_responsive() {
+        let inline = probe_p99_under_keypair_load(Route::new().at("/keypair/batch", generate_keypair_batch_inline)).await;
+        let offloaded = probe_p99_under_keypair_load(Route::new().at("/keypair/batch", generate_keypair_batch)).await;
+        assert!(offloaded < inline, "probe p99 offloaded {offloaded:?}, inline {inline:?}");
+    }
+
+    #[tokio::test]
+    async fn sign_tx_signs_legacy_and_v0_messages() {
+        let payer = Keypair::new();
+        let other = Keypair::new();
+        let ixs = [system_instruction::transfer(&payer.pubkey(), &other.pubkey(), 1)];
+        for version in ["legacy", "0"] {
+            let message = compile_message(&ixs, &payer.pubkey(), Hash::new_unique(), Some(version), &[]).unwrap();
+            let app = Route::new().at("/tx/sign", sign_tx);
+            let (status, body) = post_json(app, "/tx/sign", serde_json::json!({
+                "message": general_purpose::STANDARD.encode(message.serialize()),
+                "secrets": [payer.to_base58_string()],
+            })).await;
+            assert_eq!(status, StatusCode::OK, "{version}: {body}");
+            assert_eq!(body["data"]["complete"], true);
+            let tx = decode_transaction(body["data"]["transaction"].as_str().unwrap()).unwrap();
+            assert_eq!(tx.message, message);
+            assert!(tx.verify_with_results().iter().all(|ok| *ok));
+        }
+    }
+
+    #[tokio::test]
+    async fn sign_tx_rejects_malformed_message() {
+        let app = Route::new().at("/tx/sign", sign_tx);
+        let (status, body) = post_json(app, "/tx/sign", serde_json::json!({
+            "message": general_purpose::STANDARD.encode([0xffu8; 3]),
+            "secrets": [Keypair::new().to_base58_string()],
+        })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Invalid message");
+
+        // Decodes fine, but claims more signers than it has account keys.
+        let mut message = Message::new(&[], Some(&Keypair::new().pubkey()));
+        message.header.num_required_signatures = 5;
+        let app = Route::new().at("/tx/sign", sign_tx);
+        let (status, body) = post_json(app, "/tx/sign", serde_json::json!({
+            "message": general_purpose::STANDARD.encode(VersionedMessage::Legacy(message).serialize()),
+            "secrets": [Keypair::new().to_base58_string()],
+        })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Invalid message");
+    }
+
+    #[tokio::test]
+    async fn padded_inputs_are_trimmed_and_echoed_normalized() {
+        let keypair = Keypair::new();
+        let signature = keypair.sign_message(b"hello");
+
+        let app = Route::new().at("/message/verify", verify_message);
+        let (status, body) = post_json(app, "/message/verify", serde_json::json!({
+            "message": "hello",
+            "signature": format!(" {}\n", general_purpose::STANDARD.encode(signature)),
+            "pubkey": format!("\t{}\n", keypair.pubkey()),
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["valid"], true);
+        assert_eq!(body["data"]["pubkey"], keypair.pubkey().to_string());
+
+        let app = Route::new().at("/keypair/import", import_keypair);
+        let (status, body) = post_json(app, "/keypair/import", serde_json::json!({
+            "secret": format!("{}\n", keypair.to_base58_string()),
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["pubkey"], keypair.pubkey().to_string());
+
+        let mint = Pubkey::new_unique();
+        let app = Route::new().at("/token/ata/derive", derive_ata);
+        let (status, body) = post_json(app, "/token/ata/derive", serde_json::json!({
+            "owner": format!(" {} ", keypair.pubkey()),
+            "mint": format!("{mint}\n"),
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["owner"], keypair.pubkey().to_string());
+        assert_eq!(body["data"]["mint"], mint.to_string());
+    }
+
+    #[tokio::test]
+    async fn health_reports_without_rpc_client() {
+        // No `Arc<RpcClient>` is attached: `/health` must not need one.
+        let app = Route::new().at("/health", health).data(Arc::new(HealthState::new()));
+        let resp = app.get_response(Request::builder().uri_str("/health").finish()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = body_json(resp).await;
+        assert_eq!(body["data"]["status"], "OK");
+        assert_eq!(body["data"]["checks"]["rpc"]["status"], "unknown");
+    }
+
+    #[test]
+    fn rate_limit_of_zero_is_rejected() {
+        assert!(RateLimiter::new(0).is_err());
+        assert!(RateLimiter::new(1).is_ok());
+    }
+
+    #[test]
+    fn prune_drops_only_refilled_buckets() {
+        let limiter = RateLimiter::new(60).unwrap();
+        let busy: IpAddr = "10.0.0.1".parse().unwrap();
+        let idle: IpAddr = "10.0.0.2".parse().unwrap();
+        assert!(limiter.try_acquire(busy));
+        limiter.buckets.lock().unwrap().insert(idle, Bucket { tokens: 60.0, updated: Instant::now() });
+        limiter.prune();
+        let buckets = limiter.buckets.lock().unwrap();
+        assert!(buckets.contains_key(&busy));
+        assert!(!buckets.contains_key(&idle));
+    }
+
+    #[tokio::test]
+    async fn stalled_upload_times_out() {
+        let timeouts = RequestTimeouts {
+            default: Duration::from_millis(50),
+            vanity: Duration::from_millis(50),
+            confirm_max: Duration::from_millis(50),
+        };
+        let app = Route::new()
+            .at("/keypair/import", import_keypair)
+            .around(limit_body_size)
+            .data(MaxBodyBytes(DEFAULT_MAX_BODY_BYTES))
+            .around(enforce_timeout)
+            .data(timeouts);
+        // The writer is held open and never written to, so the body never ends.
+        let (_writer, reader) = tokio::io::duplex(64);
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri_str("/keypair/import")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_LENGTH, "64")
+            .body(Body::from_async_read(reader));
+        let resp = tokio::time::timeout(Duration::from_secs(5), app.get_response(req)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::REQUEST_TIMEOUT);
+        assert_eq!(body_json(resp).await["errorCode"], "TIMEOUT");
+    }
+
+    #[tokio::test]
+    async fn attach_signatures_rejects_inconsistent_header() {
+        let signer = Keypair::new();
+        let mut message = Message::new(&[], Some(&signer.pubkey()));
+        message.header.num_required_signatures = 5;
+        let signed_bytes = VersionedMessage::Legacy(message).serialize();
+        let app = Route::new().at("/tx/attach-signatures", attach_signatures);
+        let (status, body) = post_json(app, "/tx/attach-signatures", serde_json::json!({
+            "message": general_purpose::STANDARD.encode(&signed_bytes),
+            "signatures": [{
+                "pubkey": signer.pubkey().to_string(),
+                "signature": signer.sign_message(&signed_bytes).to_string(),
+            }],
+        })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Invalid message");
+    }
+
+    #[tokio::test]
+    async fn solana_pay_links_use_parsed_pubkeys() {
+        let recipient = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let reference = Pubkey::new_unique();
+        let app = Route::new().at("/solana-pay/transfer-url", solana_pay_transfer_url);
+        let (status, body) = post_json(app, "/solana-pay/transfer-url", serde_json::json!({
+            "recipient": format!(" {recipient}\n"),
+            "amount": "1.5",
+            "splToken": format!(" {mint}\n"),
+            "reference": format!("\t{reference} "),
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+        let url = body["data"]["url"].as_str().unwrap();
+        assert_eq!(url, format!("solana:{recipient}?amount=1.5&spl-token={mint}&reference={reference}"));
+
+        let parsed = parse_solana_pay_url(&format!("solana: {recipient}?spl-token=%20{mint}&reference={reference}%0A")).unwrap();
+        assert_eq!(parsed.recipient, Some(recipient.to_string()));
+        assert_eq!(parsed.spl_token, Some(mint.to_string()));
+        assert_eq!(parsed.reference, [reference.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn batch_expands_create_mint_full() {
+        let payer = Keypair::new().pubkey().to_string();
+        let mint = Keypair::new().pubkey().to_string();
+        let app = Route::new().at("/batch", batch);
+        let (status, body) = post_json(app, "/batch", serde_json::json!({
+            "operations": [
+                { "type": "createMintFull", "params": { "payer": payer, "mint": mint, "mintAuthority": payer, "decimals": 6 } },
+                { "type": "memo", "params": { "memo": "hi" } },
+            ],
+        })).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let programs: Vec<&str> = body["data"]["instructions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|ix| ix["programId"].as_str().unwrap())
+            .collect();
+        let system = solana_sdk::system_program::id().to_string();
+        let token = spl_token::id().to_string();
+        assert_eq!(programs[..2], [system.as_str(), token.as_str()]);
+        assert_eq!(programs.len(), 3);
+    }
+
+    #[test]
+    fn parse_sol_amount_edge_cases() {
+        assert_eq!(parse_sol_amount(".5").unwrap(), 500_000_000);
+        assert_eq!(parse_sol_amount("1.").unwrap(), LAMPORTS_PER_SOL);
+        assert_eq!(parse_sol_amount("0.000000001").unwrap(), 1);
+        assert_eq!(parse_sol_amount("18446744073.709551615").unwrap(), u64::MAX);
+        for invalid in ["", ".", "0.0000000001", "18446744073.709551616", "99999999999999999999", "-1", "1e3", " 1"] {
+            assert!(parse_sol_amount(invalid).is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn siws_message_round_trips() {
+        let address = Pubkey::new_unique().to_string();
+        let full = SiwsFields {
+            domain: "example.com".to_string(),
+            address: address.clone(),
+            statement: Some("Sign in to Example.\nSecond line.".to_string()),
+            uri: Some("https://example.com/login".to_string()),
+            version: Some("1".to_string()),
+            chain_id: Some("mainnet".to_string()),
+            nonce: Some("abc123".to_string()),
+            issued_at: Some("2024-01-01T00:00:00Z".to_string()),
+            expiration_time: Some("2024-01-02T00:00:00Z".to_string()),
+            not_before: Some("2024-01-01T00:00:00Z".to_string()),
+            request_id: Some("req-1".to_string()),
+            resources: vec!["https://example.com/a".to_string(), "ipfs://b".to_string()],
+        };
+        let minimal = SiwsFields { domain: "example.com".to_string(), address, ..Default::default() };
+        for fields in [full, minimal] {
+            let text = siws_message_text(&fields);
+            let parsed = parse_siws_message(&text).unwrap();
+            assert_eq!(serde_json::to_value(&parsed).unwrap(), serde_json::to_value(&fields).unwrap());
+            assert_eq!(siws_message_text(&parsed), text);
+            assert!(parse_siws_message(&format!("{text}\n")).is_err(), "trailing newline accepted");
+        }
+        let address = Pubkey::new_unique();
+        for invalid in [
+            String::new(),
+            format!("example.com wants you to sign in:\n{address}"),
+            format!("example.com{SIWS_HEADER_SUFFIX}\n{address}\n\nURI: https://example.com\nFoo: bar"),
+            format!("example.com{SIWS_HEADER_SUFFIX}\n{address}\n\nResources:\nnot-a-bullet"),
+        ] {
+            assert!(parse_siws_message(&invalid).is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn transfer_fee_rounds_up_and_caps() {
+        // 1 bp of 1 is 0.0001, rounded up to a whole unit.
+        assert_eq!(calculate_transfer_fee(1, 1, u64::MAX), 1);
+        assert_eq!(calculate_transfer_fee(10_000, 1, u64::MAX), 1);
+        assert_eq!(calculate_transfer_fee(10_001, 1, u64::MAX), 2);
+        assert_eq!(calculate_transfer_fee(1_000_000, 250, 10_000), 10_000);
+        assert_eq!(calculate_transfer_fee(1_000_000, 250, 100), 100);
+        assert_eq!(calculate_transfer_fee(u64::MAX, MAX_FEE_BASIS_POINTS, u64::MAX), u64::MAX);
+        assert_eq!(calculate_transfer_fee(0, 500, 100), 0);
+        assert_eq!(calculate_transfer_fee(1_000, 0, 100), 0);
+    }
+
+    #[test]
+    fn percent_encode_escapes_reserved_and_multibyte() {
+        assert_eq!(percent_encode("Aa0-._~"), "Aa0-._~");
+        assert_eq!(percent_encode("a b&c=d?"), "a%20b%26c%3Dd%3F");
+        assert_eq!(percent_encode("%"), "%25");
+        assert_eq!(percent_encode("café"), "caf%C3%A9");
+        assert_eq!(percent_encode(""), "");
+    }
+
+    #[test]
+    fn validate_pay_amount_rejects_malformed_amounts() {
+        assert!(validate_pay_amount("1.5", true).is_ok());
+        assert!(validate_pay_amount("0.123456789012", false).is_ok());
+        for invalid in ["", "-1", ".5", "1.", "1e3", "1,5"] {
+            assert!(validate_pay_amount(invalid, false).is_err(), "{invalid:?}");
+        }
+        // SOL amounts are capped at lamport precision.
+        assert!(validate_pay_amount("0.0000000001", true).is_err());
+    }
+
+    #[test]
+    fn percent_decode_rejects_truncated_and_non_utf8() {
+        assert_eq!(percent_decode("a%20b%2fc").as_deref(), Some("a b/c"));
+        assert_eq!(percent_decode("caf%C3%A9").as_deref(), Some("café"));
+        assert_eq!(percent_decode("").as_deref(), Some(""));
+        for invalid in ["%", "abc%", "abc%2", "%zz", "%C3", "%FF"] {
+            assert!(percent_decode(invalid).is_none(), "{invalid:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn solana_pay_url_round_trips_through_parser() {
+        let recipient = Pubkey::new_unique().to_string();
+        let mint = Pubkey::new_unique().to_string();
+        let references = [Pubkey::new_unique().to_string(), Pubkey::new_unique().to_string()];
+        let app = Route::new().at("/solana-pay/transfer-url", solana_pay_transfer_url);
+        let (status, body) = post_json(app, "/solana-pay/transfer-url", serde_json::json!({
+            "recipient": recipient,
+            "amount": "0.01",
+            "splToken": mint,
+            "reference": references,
+            "label": "Café & Co",
+            "message": "Order #42: 2 × latte",
+            "memo": "a=b&c",
+        })).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let parsed = parse_solana_pay_url(body["data"]["url"].as_str().unwrap()).unwrap();
+        assert_eq!(parsed.request_type, "transfer");
+        assert_eq!(parsed.recipient.as_deref(), Some(recipient.as_str()));
+        assert_eq!(parsed.amount.as_deref(), Some("0.01"));
+        assert_eq!(parsed.spl_token.as_deref(), Some(mint.as_str()));
+        assert_eq!(parsed.reference, references);
+        assert_eq!(parsed.label.as_deref(), Some("Café & Co"));
+        assert_eq!(parsed.message.as_deref(), Some("Order #42: 2 × latte"));
+        assert_eq!(parsed.memo.as_deref(), Some("a=b&c"));
+    }
+
+    #[test]
+    fn solana_pay_parser_handles_transaction_requests_and_errors() {
+        let parsed = parse_solana_pay_url("solana:https%3A%2F%2Fexample.com%2Fpay%3Fid%3D1").unwrap();
+        assert_eq!(parsed.request_type, "transaction");
+        assert_eq!(parsed.link.as_deref(), Some("https://example.com/pay?id=1"));
+        let recipient = Pubkey::new_unique();
+        for invalid in [
+            format!("bitcoin:{recipient}"),
+            "solana:".to_string(),
+            "solana:http%3A%2F%2Fexample.com".to_string(),
+            format!("solana:{recipient}?label=%"),
+            format!("solana:{recipient}?amount=1&amount=2"),
+            format!("solana:{recipient}?reference=nope"),
+            format!("solana:{recipient}?amount=-1"),
+        ] {
+            assert!(parse_solana_pay_url(&invalid).is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn decode_seed_accepts_32_bytes_in_either_encoding() {
+        let seed = [7u8; 32];
+        let base58 = seed.to_base58();
+        let base64 = general_purpose::STANDARD.encode(seed);
+        assert_eq!(decode_seed(&base58, None).unwrap(), seed);
+        assert_eq!(decode_seed(&base64, None).unwrap(), seed);
+        assert_eq!(decode_seed(&format!(" {base58}\n"), Some("base58")).unwrap(), seed);
+        assert_eq!(decode_seed(&base64, Some("base64")).unwrap(), seed);
+        assert!(decode_seed(&base64, Some("base58")).is_err());
+        assert!(decode_seed(&base58, Some("hex")).is_err());
+        let short = [7u8; 31];
+        // All `/` in base64, so it cannot also be read as base58.
+        let long = [0xffu8; 33];
+        for invalid in [short.to_base58(), general_purpose::STANDARD.encode(long), "not a seed!".to_string(), String::new()] {
+            assert!(decode_seed(&invalid, None).is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn ui_amount_to_amount_edge_cases() {
+        assert_eq!(ui_amount_to_amount("1.5", 6).unwrap(), 1_500_000);
+        assert_eq!(ui_amount_to_amount(".5", 2).unwrap(), 50);
+        assert_eq!(ui_amount_to_amount("1.", 2).unwrap(), 100);
+        assert_eq!(ui_amount_to_amount("42", 0).unwrap(), 42);
+        // Trailing zeros past `decimals` carry no value and are allowed.
+        assert_eq!(ui_amount_to_amount("1.2300", 2).unwrap(), 123);
+        assert_eq!(ui_amount_to_amount("18446744073709551615", 0).unwrap(), u64::MAX);
+        let invalid_cases = [
+            ("1.234", 2),
+            ("0.5", 0),
+            ("18446744073709551616", 0),
+            ("18446744073.8", 9),
+            ("", 6),
+            (".", 6),
+            ("-1", 6),
+            ("1e3", 6),
+        ];
+        for (invalid, decimals) in invalid_cases {
+            assert!(ui_amount_to_amount(invalid, decimals).is_err(), "{invalid:?} with {decimals} decimals");
+        }
+    }
+
+    #[tokio::test]
+    async fn convert_amount_round_trips_raw_and_ui() {
+        let app = || Route::new().at("/token/amount/convert", convert_amount);
+        let (status, body) = post_json(app(), "/token/amount/convert", serde_json::json!({ "amount": 1_500_000, "decimals": 6 })).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["data"]["uiAmount"], "1.5");
+        let (status, body) = post_json(app(), "/token/amount/convert", serde_json::json!({ "uiAmount": "1.5", "decimals": 6 })).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["data"]["amount"], "1500000");
+        let (status, _) = post_json(app(), "/token/amount/convert", serde_json::json!({ "uiAmount": "1.5000001", "decimals": 6 })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
 }